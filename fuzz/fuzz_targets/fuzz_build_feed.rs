@@ -12,7 +12,8 @@
 use arbitrary::Arbitrary;
 use libfuzzer_sys::fuzz_target;
 use mdbook_rss_feed::{
-    Article, DefaultBehavior, FeedOptions, build_feed_from_articles, parse_frontmatter,
+    Allowlist, Article, Diagnostics, FeedOptions, build_feed_from_articles,
+    parse_frontmatter_lenient,
 };
 
 #[derive(Arbitrary, Debug)]
@@ -22,10 +23,12 @@ struct Input {
     /// Simulated src-relative path (e.g. "chapter/page.md")
     path: String,
     full_preview: bool,
+    full_content: bool,
+    sanitize: bool,
 }
 
 fuzz_target!(|input: Input| {
-    let (fm, content) = parse_frontmatter(&input.raw, "fuzz-chapter", None, false);
+    let (fm, content) = parse_frontmatter_lenient(&input.raw, "fuzz-chapter", None);
 
     let articles = vec![Article {
         fm,
@@ -33,19 +36,18 @@ fuzz_target!(|input: Input| {
         path: input.path,
     }];
 
+    let allowlist = Allowlist::default();
     let opts = FeedOptions {
         title: "Fuzz Book",
-        site_url: "https://example.com",
         description: "Fuzz feed",
         full_preview: input.full_preview,
-        max_items: 0,
-        paginated: false,
-        default_behavior: DefaultBehavior::IncludeAll,
-        strict: false,
-        author_email: None,
+        full_content: input.full_content,
+        sanitize: input.sanitize.then_some(&allowlist),
+        tag_feeds: true,
+        ..FeedOptions::new("https://example.com")
     };
 
-    let result = build_feed_from_articles(articles, &opts);
+    let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
 
     // Must always produce at least one page.
     assert!(
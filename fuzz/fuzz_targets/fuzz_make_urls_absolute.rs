@@ -1,8 +1,8 @@
 //! Fuzz target for `make_urls_absolute`.
 //!
-//! Feeds arbitrary HTML strings and base URLs. The function must never panic
-//! and must always return a string at least as long as the input (since it
-//! only ever adds content, never removes it).
+//! Feeds arbitrary HTML strings and base URLs. The function must never panic.
+//! Output may be shorter than the input: `..` segments are resolved away and
+//! `srcset` whitespace is normalized.
 //!
 //! Run with:
 //!   cargo fuzz run fuzz_make_urls_absolute
@@ -21,12 +21,5 @@ struct Input {
 }
 
 fuzz_target!(|input: Input| {
-    let out = make_urls_absolute(&input.html, &input.base_url, input.page_url.as_deref());
-
-    // Output must never be shorter than input: we only expand URLs, never
-    // remove content.
-    assert!(
-        out.len() >= input.html.len(),
-        "output should not be shorter than input html"
-    );
+    let _ = make_urls_absolute(&input.html, &input.base_url, input.page_url.as_deref());
 });
@@ -1,9 +1,11 @@
-//! Fuzz target for `parse_frontmatter`
+//! Fuzz target for `parse_frontmatter_lenient` and `try_parse_frontmatter`
 #![no_main]
 
+use std::path::Path;
+
 use arbitrary::Arbitrary;
 use libfuzzer_sys::fuzz_target;
-use mdbook_rss_feed::parse_frontmatter;
+use mdbook_rss_feed::{parse_frontmatter_lenient, try_parse_frontmatter};
 
 #[derive(Arbitrary, Debug)]
 struct Input {
@@ -13,8 +15,7 @@ struct Input {
     title_hint: String,
 }
 fuzz_target!(|input: Input| {
-    // `strict = false` so we never call `process::exit` during fuzzing.
-    let (fm, body) = parse_frontmatter(&input.raw, &input.title_hint, None, false);
+    let (fm, body) = parse_frontmatter_lenient(&input.raw, &input.title_hint, None);
 
     // Title must never be empty: falls back to title_hint, first # heading,
     // or "Untitled" when all three are absent.
@@ -23,4 +24,13 @@ fuzz_target!(|input: Input| {
     // Use both outputs to prevent the optimizer removing the call entirely.
     let _ = body.len();
     let _ = fm.date;
+
+    // The fallible variant must agree with the lenient one whenever it
+    // succeeds, and report errors instead of panicking or exiting.
+    if let Ok((strict_fm, strict_body)) =
+        try_parse_frontmatter(&input.raw, Path::new("fuzz.md"), &input.title_hint, None)
+    {
+        assert_eq!(strict_fm.title, fm.title);
+        assert_eq!(strict_body, body);
+    }
 });
@@ -8,8 +8,10 @@
 //!   `SUMMARY.md` are present. **Prefer this path.**
 //!
 //! - [`collect_articles`]: walks the `src/` directory on disk. Kept for
-//!   standalone/testing use. Does **not** expand `{{#include}}` directives
-//!   and does **not** filter to `SUMMARY.md` entries.
+//!   standalone/testing use. Expands `{{#include}}` and the other mdBook
+//!   directives itself, and does **not** filter to `SUMMARY.md` entries.
+//!
+//! Both take a [`DateSource`] for chapters without a frontmatter `date`.
 
 use std::{fs, path::Path, time::SystemTime};
 
@@ -17,8 +19,12 @@ use chrono::{DateTime, Utc};
 use serde_json::Value;
 use walkdir::WalkDir;
 
+use crate::date_source::DateSource;
+use crate::diagnostics::{DiagnosticKind, Diagnostics};
+use crate::directives::expand_directives;
 use crate::error::{FeedError, Result};
-use crate::frontmatter::{FrontMatter, parse_frontmatter};
+use crate::frontmatter::{FrontMatter, parse_chapter_frontmatter};
+use crate::preview::strip_excerpt_separator;
 
 /// Convert file modification time to UTC.
 fn systemtime_to_utc(st: SystemTime) -> DateTime<Utc> {
@@ -30,7 +36,7 @@ fn systemtime_to_utc(st: SystemTime) -> DateTime<Utc> {
 /// `Article` holds the frontmatter, full Markdown body, and the path
 /// relative to the mdBook `src` root. It is the internal representation
 /// used before converting to RSS items.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Article {
     pub fm: FrontMatter,
     pub content: String,
@@ -41,8 +47,16 @@ pub struct Article {
 // ── Book JSON path ────────────────────────────────────────────────────────────
 
 /// Recursively walk a `BookItem` JSON array and collect chapters.
-fn walk_book_items(items: &Value, out: &mut Vec<Article>, strict: bool) {
-    let Some(arr) = items.as_array() else { return };
+fn walk_book_items(
+    items: &Value,
+    out: &mut Vec<Article>,
+    strict: bool,
+    dates: &DateSource,
+    diagnostics: &mut Diagnostics,
+) -> Result<()> {
+    let Some(arr) = items.as_array() else {
+        return Ok(());
+    };
 
     for item in arr {
         // Only Chapter variants carry content; Separator and PartTitle are skipped.
@@ -77,7 +91,17 @@ fn walk_book_items(items: &Value, out: &mut Vec<Article>, strict: bool) {
             continue;
         }
 
-        let (fm, body) = parse_frontmatter(&content, &name, None, strict);
+        // There is no file on disk to take an mtime from.
+        let (mut fm, body) = parse_chapter_frontmatter(
+            &content,
+            Path::new(&path),
+            &path,
+            &name,
+            dates.created(&path, None),
+            strict,
+            diagnostics,
+        )?;
+        fm.updated = fm.updated.or_else(|| dates.updated(&path));
 
         out.push(Article {
             fm,
@@ -87,9 +111,10 @@ fn walk_book_items(items: &Value, out: &mut Vec<Article>, strict: bool) {
 
         // Recurse into nested chapters.
         if let Some(sub) = chapter.get("sub_items") {
-            walk_book_items(sub, out, strict);
+            walk_book_items(sub, out, strict, dates, diagnostics)?;
         }
     }
+    Ok(())
 }
 
 /// Collect articles from the book JSON object mdBook passes to preprocessors.
@@ -98,66 +123,156 @@ fn walk_book_items(items: &Value, out: &mut Vec<Article>, strict: bool) {
 /// chapters listed in `SUMMARY.md`, and all `{{#include}}` directives in
 /// chapter content have already been expanded by mdBook before this
 /// preprocessor is called.
-#[must_use]
-pub fn articles_from_book_json(book_json: &Value, strict: bool) -> Vec<Article> {
+///
+/// Undated chapters get no date with [`DateSource::Mtime`], since the
+/// book JSON carries no file times; use [`DateSource::Git`] to date them.
+///
+/// # Errors
+/// In `strict` mode, returns [`FeedError::Frontmatter`] for the first
+/// chapter whose frontmatter cannot be parsed. Otherwise such chapters are
+/// recorded in `diagnostics` and kept with default metadata.
+pub fn articles_from_book_json(
+    book_json: &Value,
+    strict: bool,
+    dates: &DateSource,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<Article>> {
     let mut articles = Vec::new();
 
     // mdBook's Book serialises its chapters under "items".
     if let Some(items) = book_json.get("items") {
-        walk_book_items(items, &mut articles, strict);
+        walk_book_items(items, &mut articles, strict, dates, diagnostics)?;
     }
 
     // Sort newest → oldest; None dates fall last.
     articles.sort_by_key(|b| std::cmp::Reverse(b.fm.date));
 
-    articles
+    Ok(articles)
+}
+
+/// Remove the excerpt marker line from every chapter in the book JSON, so
+/// the rendered book never shows it.
+///
+/// Call this on the book a preprocessor echoes back to mdBook, after the
+/// articles have been collected from it.
+pub fn strip_excerpt_separators(book_json: &mut Value, separator: &str) {
+    fn walk(items: &mut Value, separator: &str) {
+        let Some(arr) = items.as_array_mut() else {
+            return;
+        };
+        for chapter in arr.iter_mut().filter_map(|item| item.get_mut("Chapter")) {
+            if let Some(content) = chapter.get_mut("content")
+                && let Some(stripped) = content
+                    .as_str()
+                    .and_then(|md| strip_excerpt_separator(md, separator))
+            {
+                *content = Value::String(stripped);
+            }
+            if let Some(sub) = chapter.get_mut("sub_items") {
+                walk(sub, separator);
+            }
+        }
+    }
+
+    if let Some(items) = book_json.get_mut("items") {
+        walk(items, separator);
+    }
 }
 
 // ── Filesystem path (legacy / standalone) ────────────────────────────────────
 
 /// Parses a markdown file and returns an [`Article`].
 ///
+/// Without a frontmatter `date`, the date comes from `dates`: the file's
+/// modification time, or its first commit.
+///
 /// # Errors
 /// Returns `Err` if `path` can't be read, or if it has no usable file stem
-/// (e.g. it's a directory or has no filename).
-pub fn parse_markdown_file(root: &Path, path: &Path, strict: bool) -> Result<Article> {
+/// (e.g. it's a directory or has no filename). In `strict` mode, invalid
+/// frontmatter is reported as [`FeedError::Frontmatter`]; otherwise it is
+/// recorded in `diagnostics`.
+pub fn parse_markdown_file(
+    root: &Path,
+    path: &Path,
+    strict: bool,
+    dates: &DateSource,
+    diagnostics: &mut Diagnostics,
+) -> Result<Article> {
     let text = fs::read_to_string(path).map_err(|source| FeedError::Io {
         path: path.to_path_buf(),
         source,
     })?;
 
-    let fallback_date = path
-        .metadata()
-        .ok()
-        .and_then(|m| m.modified().ok())
-        .map(systemtime_to_utc);
-
     let title_hint = path.file_stem().map_or_else(
         || "untitled".to_string(),
         |s| s.to_string_lossy().into_owned(),
     );
 
-    let (fm, content) = parse_frontmatter(&text, &title_hint, fallback_date, strict);
+    let rel_path = path
+        .strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned();
+    let mtime = path
+        .metadata()
+        .ok()
+        .and_then(|m| m.modified().ok())
+        .map(systemtime_to_utc);
+    let (mut fm, content) = parse_chapter_frontmatter(
+        &text,
+        path,
+        &rel_path,
+        &title_hint,
+        dates.created(&rel_path, mtime),
+        strict,
+        diagnostics,
+    )?;
+    fm.updated = fm.updated.or_else(|| dates.updated(&rel_path));
 
-    let rel_path = path.strip_prefix(root).unwrap_or(path);
     Ok(Article {
         fm,
         content,
-        path: rel_path.to_string_lossy().into_owned(),
+        path: rel_path,
     })
 }
 
+/// Expand the mdBook directives in a chapter read from `path`, relative to
+/// its own directory. mdBook has already done this for book JSON chapters,
+/// where an unescaped `{{#include}}` is meant as text.
+fn expand_chapter_directives(article: &mut Article, path: &Path, diagnostics: &mut Diagnostics) {
+    let (content, unresolved) = expand_directives(&article.content, path.parent());
+    article.content = content;
+    for file in unresolved {
+        diagnostics.warn(
+            &article.path,
+            DiagnosticKind::UnresolvedInclude,
+            format!(
+                "can't read included file `{}`, leaving it out",
+                file.display()
+            ),
+        );
+    }
+}
+
 /// Collect all Markdown chapters under `src_dir`.
 ///
 /// Walks the directory tree, skipping `SUMMARY.md` and non-Markdown files,
-/// parses each chapter into an [`Article`], then sorts the list newest →
-/// oldest based on frontmatter `date` (falling back to file modification
-/// time). Files that fail to parse are skipped rather than aborting the
-/// whole scan.
+/// parses each chapter into an [`Article`] with its mdBook directives
+/// expanded (see [`expand_directives`]), then sorts the list newest →
+/// oldest based on frontmatter `date` (falling back to `dates`). Files that
+/// fail to parse are skipped and recorded in `diagnostics` rather than
+/// aborting the whole scan, unless `strict` is set.
 ///
 /// # Errors
-/// Returns `Err` if `src_dir` doesn't exist or can't be walked.
-pub fn collect_articles(src_dir: &Path, strict: bool) -> Result<Vec<Article>> {
+/// Returns `Err` if `src_dir` doesn't exist or can't be walked. In `strict`
+/// mode, also returns the first error from [`parse_markdown_file`], such as
+/// [`FeedError::Frontmatter`].
+pub fn collect_articles(
+    src_dir: &Path,
+    strict: bool,
+    dates: &DateSource,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<Article>> {
     let mut articles = Vec::new();
 
     for entry in WalkDir::new(src_dir) {
@@ -186,8 +301,20 @@ pub fn collect_articles(src_dir: &Path, strict: bool) -> Result<Vec<Article>> {
             continue;
         }
 
-        if let Ok(article) = parse_markdown_file(src_dir, path, strict) {
-            articles.push(article);
+        match parse_markdown_file(src_dir, path, strict, dates, diagnostics) {
+            Ok(mut article) => {
+                expand_chapter_directives(&mut article, path, diagnostics);
+                articles.push(article);
+            }
+            Err(e) if strict => return Err(e),
+            Err(e) => {
+                let rel_path = path.strip_prefix(src_dir).unwrap_or(path);
+                diagnostics.warn(
+                    &rel_path.to_string_lossy(),
+                    DiagnosticKind::UnreadableFile,
+                    format!("{e}; skipped"),
+                );
+            }
         }
     }
 
@@ -226,13 +353,33 @@ mod tests {
                 chapter_item("My Post", "---\ntitle: My Post\ndate: 2024-01-15\n---\n\nHello world.", "posts/hello.md")
             ]
         });
-        let articles = articles_from_book_json(&book, false);
+        let articles =
+            articles_from_book_json(&book, false, &DateSource::Mtime, &mut Diagnostics::new())
+                .unwrap();
         assert_eq!(articles.len(), 1);
         assert_eq!(articles[0].fm.title, "My Post");
         assert_eq!(articles[0].path, "posts/hello.md");
         assert!(articles[0].content.contains("Hello world."));
     }
 
+    #[test]
+    fn strip_excerpt_separators_rewrites_nested_chapters() {
+        let mut nested = chapter_item("Parent", "Intro\n<!-- more -->\nRest", "parent.md");
+        nested["Chapter"]["sub_items"] = json!([chapter_item(
+            "Child",
+            "Teaser\n<!-- more -->\nBody",
+            "child.md"
+        )]);
+        let mut book =
+            json!({ "items": [nested, chapter_item("Plain", "No marker.", "plain.md")] });
+        strip_excerpt_separators(&mut book, "<!-- more -->");
+
+        let parent = &book["items"][0]["Chapter"];
+        assert_eq!(parent["content"], "Intro\nRest");
+        assert_eq!(parent["sub_items"][0]["Chapter"]["content"], "Teaser\nBody");
+        assert_eq!(book["items"][1]["Chapter"]["content"], "No marker.");
+    }
+
     #[test]
     fn articles_from_book_json_skips_separators_and_part_titles() {
         let book = json!({
@@ -242,7 +389,9 @@ mod tests {
                 chapter_item("Real Chapter", "Content.", "chapter.md")
             ]
         });
-        let articles = articles_from_book_json(&book, false);
+        let articles =
+            articles_from_book_json(&book, false, &DateSource::Mtime, &mut Diagnostics::new())
+                .unwrap();
         assert_eq!(articles.len(), 1);
         assert_eq!(articles[0].path, "chapter.md");
     }
@@ -263,7 +412,9 @@ mod tests {
                 chapter_item("Published", "Content.", "published.md")
             ]
         });
-        let articles = articles_from_book_json(&book, false);
+        let articles =
+            articles_from_book_json(&book, false, &DateSource::Mtime, &mut Diagnostics::new())
+                .unwrap();
         assert_eq!(articles.len(), 1);
         assert_eq!(articles[0].path, "published.md");
     }
@@ -284,7 +435,9 @@ mod tests {
                 }
             ]
         });
-        let articles = articles_from_book_json(&book, false);
+        let articles =
+            articles_from_book_json(&book, false, &DateSource::Mtime, &mut Diagnostics::new())
+                .unwrap();
         assert_eq!(articles.len(), 2);
         let paths: Vec<&str> = articles.iter().map(|a| a.path.as_str()).collect();
         assert!(paths.contains(&"parent.md"));
@@ -300,7 +453,9 @@ mod tests {
                 chapter_item("Mid", "---\ndate: 2023-03-15\n---\nMid.", "mid.md"),
             ]
         });
-        let articles = articles_from_book_json(&book, false);
+        let articles =
+            articles_from_book_json(&book, false, &DateSource::Mtime, &mut Diagnostics::new())
+                .unwrap();
         assert_eq!(articles.len(), 3);
         assert_eq!(articles[0].path, "new.md");
         assert_eq!(articles[1].path, "mid.md");
@@ -315,7 +470,9 @@ mod tests {
                 chapter_item("Dated", "---\ndate: 2024-01-01\n---\nDated.", "dated.md"),
             ]
         });
-        let articles = articles_from_book_json(&book, false);
+        let articles =
+            articles_from_book_json(&book, false, &DateSource::Mtime, &mut Diagnostics::new())
+                .unwrap();
         assert_eq!(articles.len(), 2);
         assert_eq!(articles[0].path, "dated.md");
         assert_eq!(articles[1].path, "undated.md");
@@ -324,14 +481,18 @@ mod tests {
     #[test]
     fn articles_from_book_json_empty_book_returns_empty_vec() {
         let book = json!({ "items": [] });
-        let articles = articles_from_book_json(&book, false);
+        let articles =
+            articles_from_book_json(&book, false, &DateSource::Mtime, &mut Diagnostics::new())
+                .unwrap();
         assert!(articles.is_empty());
     }
 
     #[test]
     fn articles_from_book_json_missing_items_key_returns_empty() {
         let book = json!({});
-        let articles = articles_from_book_json(&book, false);
+        let articles =
+            articles_from_book_json(&book, false, &DateSource::Mtime, &mut Diagnostics::new())
+                .unwrap();
         assert!(articles.is_empty());
     }
 
@@ -348,10 +509,71 @@ mod tests {
                 }
             }]
         });
-        let articles = articles_from_book_json(&book, false);
+        let articles =
+            articles_from_book_json(&book, false, &DateSource::Mtime, &mut Diagnostics::new())
+                .unwrap();
         assert_eq!(articles[0].path, "actual/source.md");
     }
 
+    #[test]
+    fn articles_from_book_json_strict_returns_frontmatter_error() {
+        let book = json!({
+            "items": [
+                chapter_item("Good", "Content.", "good.md"),
+                chapter_item("Bad", "---\nfeed: sometimes\n---\nBody.", "posts/bad.md"),
+            ]
+        });
+        let err = articles_from_book_json(&book, true, &DateSource::Mtime, &mut Diagnostics::new())
+            .unwrap_err();
+        assert!(
+            matches!(&err, FeedError::Frontmatter { path, line: 2, .. } if path == Path::new("posts/bad.md")),
+            "{err:?}"
+        );
+        // Non-strict keeps the chapter with default metadata and records it.
+        let mut diagnostics = Diagnostics::new();
+        let articles =
+            articles_from_book_json(&book, false, &DateSource::Mtime, &mut diagnostics).unwrap();
+        assert_eq!(articles.len(), 2);
+        let recorded: Vec<_> = diagnostics.iter().collect();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].path, "posts/bad.md");
+        assert_eq!(recorded[0].kind, DiagnosticKind::InvalidFrontmatter);
+    }
+
+    #[test]
+    fn articles_from_book_json_dates_undated_chapters_from_git() {
+        let history = crate::date_source::GitHistory::parse_log(
+            "\u{1e}2024-06-01T00:00:00Z\n\nposts/a.md\nposts/b.md\n\
+             \u{1e}2024-01-01T00:00:00Z\n\nposts/a.md\nposts/b.md\n",
+        );
+        let book = json!({
+            "items": [
+                chapter_item("A", "# A\n\nUndated.", "posts/a.md"),
+                chapter_item("B", "---\ndate: 2023-05-05\n---\nDated.", "posts/b.md"),
+            ]
+        });
+        let articles = articles_from_book_json(
+            &book,
+            false,
+            &DateSource::Git(history),
+            &mut Diagnostics::new(),
+        )
+        .unwrap();
+        let a = articles.iter().find(|a| a.path == "posts/a.md").unwrap();
+        assert_eq!(a.fm.date.unwrap().to_rfc3339(), "2024-01-01T00:00:00+00:00");
+        assert_eq!(
+            a.fm.updated.unwrap().to_rfc3339(),
+            "2024-06-01T00:00:00+00:00"
+        );
+        // An explicit date wins; the revision date still comes from git.
+        let b = articles.iter().find(|a| a.path == "posts/b.md").unwrap();
+        assert_eq!(b.fm.date.unwrap().to_rfc3339(), "2023-05-05T00:00:00+00:00");
+        assert_eq!(
+            b.fm.updated.unwrap().to_rfc3339(),
+            "2024-06-01T00:00:00+00:00"
+        );
+    }
+
     // ── parse_markdown_file ───────────────────────────────────────────────────
 
     fn write_temp_file(dir: &std::path::Path, name: &str, content: &str) -> PathBuf {
@@ -368,7 +590,14 @@ mod tests {
             "post.md",
             "---\ntitle: Hello\ndate: 2024-01-01\n---\n\nBody text here.",
         );
-        let article = parse_markdown_file(dir.path(), &path, false).unwrap();
+        let article = parse_markdown_file(
+            dir.path(),
+            &path,
+            false,
+            &DateSource::Mtime,
+            &mut Diagnostics::new(),
+        )
+        .unwrap();
         assert_eq!(article.fm.title, "Hello");
         assert!(article.content.contains("Body text here."));
         assert_eq!(article.path, "post.md");
@@ -378,7 +607,14 @@ mod tests {
     fn parse_markdown_file_uses_stem_as_title_fallback() {
         let dir = tempfile::tempdir().unwrap();
         let path = write_temp_file(dir.path(), "my-chapter.md", "No frontmatter here.");
-        let article = parse_markdown_file(dir.path(), &path, false).unwrap();
+        let article = parse_markdown_file(
+            dir.path(),
+            &path,
+            false,
+            &DateSource::Mtime,
+            &mut Diagnostics::new(),
+        )
+        .unwrap();
         // Title falls back to file stem when there's no frontmatter or h1.
         assert_eq!(article.fm.title, "my-chapter");
     }
@@ -389,7 +625,14 @@ mod tests {
         let subdir = dir.path().join("subdir");
         fs::create_dir(&subdir).unwrap();
         let path = write_temp_file(&subdir, "nested.md", "Content.");
-        let article = parse_markdown_file(dir.path(), &path, false).unwrap();
+        let article = parse_markdown_file(
+            dir.path(),
+            &path,
+            false,
+            &DateSource::Mtime,
+            &mut Diagnostics::new(),
+        )
+        .unwrap();
         // Path should be relative: "subdir/nested.md"
         assert!(!article.path.starts_with('/'));
         assert!(article.path.contains("nested.md"));
@@ -399,7 +642,13 @@ mod tests {
     fn parse_markdown_file_nonexistent_returns_err() {
         let dir = tempfile::tempdir().unwrap();
         let missing = dir.path().join("ghost.md");
-        let result = parse_markdown_file(dir.path(), &missing, false);
+        let result = parse_markdown_file(
+            dir.path(),
+            &missing,
+            false,
+            &DateSource::Mtime,
+            &mut Diagnostics::new(),
+        );
         assert!(result.is_err());
     }
 
@@ -418,7 +667,13 @@ mod tests {
             "b.md",
             "---\ntitle: B\ndate: 2024-01-01\n---\nB content.",
         );
-        let articles = collect_articles(dir.path(), false).unwrap();
+        let articles = collect_articles(
+            dir.path(),
+            false,
+            &DateSource::Mtime,
+            &mut Diagnostics::new(),
+        )
+        .unwrap();
         assert_eq!(articles.len(), 2);
         // Sorted newest first.
         assert_eq!(articles[0].fm.title, "A");
@@ -434,7 +689,13 @@ mod tests {
             "# Summary\n\n- [Chapter](chapter.md)",
         );
         write_temp_file(dir.path(), "chapter.md", "# Chapter\n\nContent.");
-        let articles = collect_articles(dir.path(), false).unwrap();
+        let articles = collect_articles(
+            dir.path(),
+            false,
+            &DateSource::Mtime,
+            &mut Diagnostics::new(),
+        )
+        .unwrap();
         assert_eq!(articles.len(), 1);
         assert_eq!(articles[0].fm.title, "Chapter");
     }
@@ -445,7 +706,13 @@ mod tests {
         write_temp_file(dir.path(), "image.png", "fake png bytes");
         write_temp_file(dir.path(), "style.css", "body { color: red; }");
         write_temp_file(dir.path(), "real.md", "# Real\n\nContent.");
-        let articles = collect_articles(dir.path(), false).unwrap();
+        let articles = collect_articles(
+            dir.path(),
+            false,
+            &DateSource::Mtime,
+            &mut Diagnostics::new(),
+        )
+        .unwrap();
         assert_eq!(articles.len(), 1);
     }
 
@@ -453,15 +720,48 @@ mod tests {
     fn collect_articles_accepts_markdown_extension() {
         let dir = tempfile::tempdir().unwrap();
         write_temp_file(dir.path(), "post.markdown", "# Long Ext\n\nContent.");
-        let articles = collect_articles(dir.path(), false).unwrap();
+        let articles = collect_articles(
+            dir.path(),
+            false,
+            &DateSource::Mtime,
+            &mut Diagnostics::new(),
+        )
+        .unwrap();
         assert_eq!(articles.len(), 1);
         assert_eq!(articles[0].fm.title, "Long Ext");
     }
 
+    #[test]
+    fn collect_articles_strict_propagates_frontmatter_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        write_temp_file(dir.path(), "good.md", "# Good\n\nContent.");
+        write_temp_file(dir.path(), "bad.md", "---\ntitle: [unclosed\n---\nBody.");
+        let err = collect_articles(
+            dir.path(),
+            true,
+            &DateSource::Mtime,
+            &mut Diagnostics::new(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, FeedError::Frontmatter { .. }), "{err:?}");
+        assert!(err.to_string().contains("bad.md"));
+        assert_eq!(
+            collect_articles(
+                dir.path(),
+                false,
+                &DateSource::Mtime,
+                &mut Diagnostics::new()
+            )
+            .unwrap()
+            .len(),
+            2
+        );
+    }
+
     #[test]
     fn collect_articles_nonexistent_dir_returns_err() {
         let path = PathBuf::from("/tmp/surely_does_not_exist_mdbook_rss_feed_test");
-        let result = collect_articles(&path, false);
+        let result = collect_articles(&path, false, &DateSource::Mtime, &mut Diagnostics::new());
         assert!(result.is_err());
     }
 }
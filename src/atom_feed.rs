@@ -1,63 +1,72 @@
 //! Atom 1.0 support.
 //!
-//! Enabled by the `atom` cargo feature. Converts an RSS [`Channel`] into a
-//! best-effort Atom 1.0 feed: titles, links, descriptions (as HTML content),
-//! and dates are copied across where available.
+//! Enabled by the `atom` cargo feature. Renders a [`Feed`] page as an Atom
+//! 1.0 feed: the chapter `date` becomes `<published>`, `updated` becomes
+//! `<updated>` (falling back to `<published>`), and author emails are kept.
+//! [`rss_to_atom`] does the same starting from an RSS [`Channel`].
 
 use atom_syndication::{
-    Content as AtomContent, Entry as AtomEntry, Feed as AtomFeed, Link as AtomLink,
-    Person as AtomPerson, Text as AtomText,
+    Category as AtomCategory, Content as AtomContent, Entry as AtomEntry, Feed as AtomFeed,
+    FixedDateTime, Link as AtomLink, Person as AtomPerson, Text as AtomText,
 };
 use chrono::DateTime;
 use rss::Channel;
 
-/// Stable per-entry id: prefer guid, then link, then title.
-fn entry_id(item: &rss::Item) -> String {
-    item.guid()
-        .map(|g| g.value().to_string())
-        .or_else(|| item.link().map(str::to_string))
-        .unwrap_or_else(|| item.title().unwrap_or_default().to_string())
-}
+use crate::error::Result;
+use crate::format::{FeedFormat, PageLinks, numbered_file};
+use crate::model::{Feed, FeedEntry};
 
-fn build_entry(item: &rss::Item) -> AtomEntry {
+fn build_entry(item: &FeedEntry) -> AtomEntry {
     let mut entry = AtomEntry::default();
-    entry.set_id(entry_id(item));
-
-    if let Some(title) = item.title() {
-        entry.set_title(title.to_string());
-    }
-    if let Some(link) = item.link() {
+    entry.set_id(item.id.clone());
+    entry.set_title(item.title.clone());
+    if let Some(link) = &item.url {
         entry.set_links(vec![AtomLink {
-            href: link.to_string(),
+            href: link.clone(),
             ..Default::default()
         }]);
     }
+    if let Some(summary) = &item.summary_html {
+        entry.set_summary(Some(AtomText::html(summary.clone())));
+    }
     // Only set content when it's non-empty
     // an empty <content> element causes validation warnings.
-    if let Some(desc) = item.description()
-        && !desc.is_empty()
-    {
+    if !item.content_html.is_empty() {
         let mut content = AtomContent::default();
         content.set_content_type("html".to_string());
-        content.set_value(Some(desc.to_string()));
+        content.set_value(Some(item.content_html.clone()));
         entry.set_content(Some(content));
     }
-    if let Some(dt) = item
-        .pub_date()
-        .and_then(|d| DateTime::parse_from_rfc2822(d).ok())
-    {
-        entry.set_updated(dt);
-    } else {
-        entry.set_updated(fallback_updated());
-    }
+    let published = item.published.map(|dt| dt.fixed_offset());
+    entry.set_published(published);
+    entry.set_updated(
+        item.updated
+            .map(|dt| dt.fixed_offset())
+            .or(published)
+            .unwrap_or_else(fallback_updated),
+    );
 
-    // Set per-entry author from RSS `<author>` field if present
-    if let Some(author) = item.author() {
-        entry.set_authors(vec![AtomPerson {
-            name: author.to_string(),
-            ..Default::default()
-        }]);
-    }
+    entry.set_categories(
+        item.categories
+            .iter()
+            .map(|c| AtomCategory {
+                term: c.clone(),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    entry.set_authors(
+        item.authors
+            .iter()
+            .map(|p| AtomPerson {
+                name: p.name.clone(),
+                email: p.email.clone(),
+                uri: p.url.clone(),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>(),
+    );
 
     entry
 }
@@ -67,15 +76,16 @@ fn build_entry(item: &rss::Item) -> AtomEntry {
 ///  The Atom spec requires a feed-level `<updated>` element. Using the Unix
 /// epoch (`1970-01-01`) triggers a validator warning ("implausible date"), so
 /// we fall back to a fixed but reasonable date instead.
-fn fallback_updated() -> DateTime<chrono::FixedOffset> {
+fn fallback_updated() -> FixedDateTime {
     DateTime::parse_from_rfc3339("2000-01-01T00:00:00Z")
         .expect("hardcoded RFC3339 date is always valid")
 }
 
 /// Convert an RSS 2.0 channel into a minimal Atom 1.0 feed.
 ///
-/// This is a best-effort mapping. It copies titles, links, descriptions (as
-/// HTML content), and dates where available.
+/// Kept for compatibility: this goes through [`Feed::from_channel`], so only
+/// what RSS can carry survives. Prefer [`feed_to_atom`] with
+/// [`crate::FeedPage::feed`].
 #[must_use]
 pub fn rss_to_atom(
     channel: &Channel,
@@ -84,7 +94,28 @@ pub fn rss_to_atom(
     prev_url: Option<&str>,
     authors: &[String],
 ) -> AtomFeed {
-    let entries: Vec<AtomEntry> = channel.items().iter().map(build_entry).collect();
+    feed_to_atom(
+        &Feed::from_channel(channel),
+        self_url,
+        next_url,
+        prev_url,
+        authors,
+    )
+}
+
+/// Render a [`Feed`] page as an Atom 1.0 feed.
+///
+/// `authors` are the feed-level authors (the book's `authors`), which Atom
+/// requires when entries have none of their own.
+#[must_use]
+pub fn feed_to_atom(
+    source: &Feed,
+    self_url: Option<&str>,
+    next_url: Option<&str>,
+    prev_url: Option<&str>,
+    authors: &[String],
+) -> AtomFeed {
+    let entries: Vec<AtomEntry> = source.entries.iter().map(build_entry).collect();
 
     // Set feed-level updated to the most recent entry date.
     // Fall back to plausible date rather then the Unix epoch
@@ -95,7 +126,7 @@ pub fn rss_to_atom(
         .unwrap_or_else(fallback_updated);
 
     let mut feed = AtomFeed::default();
-    feed.set_title(channel.title().to_string());
+    feed.set_title(source.title.clone());
     feed.set_updated(latest);
     feed.set_entries(entries);
 
@@ -112,7 +143,7 @@ pub fn rss_to_atom(
         );
     }
 
-    let home = channel.link();
+    let home = source.home_url.as_str();
 
     // Build the links vec: self, then optional next/prev, then the home link.
     let mut links: Vec<AtomLink> = Vec::new();
@@ -127,7 +158,7 @@ pub fn rss_to_atom(
     } else if !home.is_empty() {
         feed.set_id(home.to_string());
     } else {
-        feed.set_id(channel.title().to_string());
+        feed.set_id(source.title.clone());
     }
 
     if let Some(next) = next_url {
@@ -156,7 +187,7 @@ pub fn rss_to_atom(
 
     feed.set_links(links);
 
-    let desc = channel.description();
+    let desc = source.description.as_str();
     if !desc.is_empty() {
         feed.set_subtitle(Some(AtomText {
             value: desc.to_string(),
@@ -166,3 +197,105 @@ pub fn rss_to_atom(
 
     feed
 }
+
+/// Atom 1.0: `atom.xml`, `atom2.xml`, ... (`<stem>.atom`) with
+/// `rel="next"`/`rel="prev"` links.
+#[derive(Debug, Clone, Default)]
+pub struct AtomFormat {
+    /// Feed-level authors, see [`feed_to_atom`].
+    pub authors: Vec<String>,
+}
+
+impl FeedFormat for AtomFormat {
+    fn name(&self) -> &'static str {
+        "Atom"
+    }
+
+    fn file_name(&self, stem: Option<&str>, page_idx: usize) -> String {
+        match stem {
+            Some(stem) => numbered_file(stem, "atom", page_idx),
+            None => numbered_file("atom", "xml", page_idx),
+        }
+    }
+
+    fn render(&self, feed: &Feed, links: &PageLinks) -> Result<String> {
+        Ok(feed_to_atom(
+            feed,
+            Some(&links.self_url),
+            links.next_url.as_deref(),
+            links.prev_url.as_deref(),
+            &self.authors,
+        )
+        .to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Person;
+
+    fn entry() -> FeedEntry {
+        FeedEntry {
+            id: "https://example.com/a.html".to_string(),
+            title: "A".to_string(),
+            url: Some("https://example.com/a.html".to_string()),
+            content_html: "<p>Body</p>".to_string(),
+            summary_html: None,
+            published: None,
+            updated: None,
+            authors: Vec::new(),
+            categories: Vec::new(),
+        }
+    }
+
+    fn render(entries: Vec<FeedEntry>) -> String {
+        let feed = Feed {
+            title: "Book".to_string(),
+            description: String::new(),
+            home_url: "https://example.com/".to_string(),
+            entries,
+        };
+        feed_to_atom(&feed, None, None, None, &[]).to_string()
+    }
+
+    #[test]
+    fn authors_keep_email_and_uri() {
+        let mut item = entry();
+        item.authors = vec![
+            Person {
+                name: "Jane".to_string(),
+                email: Some("jane@example.com".to_string()),
+                url: Some("https://jane.example.com".to_string()),
+                avatar: None,
+            },
+            Person::named("Joe"),
+        ];
+        let xml = render(vec![item]);
+        assert!(
+            xml.contains(
+                "<author><name>Jane</name><email>jane@example.com</email>\
+                 <uri>https://jane.example.com</uri></author>"
+            ),
+            "{xml}"
+        );
+        assert!(xml.contains("<author><name>Joe</name></author>"), "{xml}");
+    }
+
+    #[test]
+    fn excerpt_goes_in_summary_and_full_html_in_content() {
+        let mut item = entry();
+        item.content_html = "<p>Whole chapter</p>".to_string();
+        item.summary_html = Some("<p>Excerpt</p>".to_string());
+        let xml = render(vec![item, entry()]);
+        assert!(
+            xml.contains(
+                "<summary type=\"html\">&lt;p&gt;Excerpt&lt;/p&gt;</summary>\
+                 <content type=\"html\">&lt;p&gt;Whole chapter&lt;/p&gt;</content>"
+            ),
+            "{xml}"
+        );
+        // Without an excerpt there's only the content.
+        assert_eq!(xml.matches("<summary").count(), 1, "{xml}");
+    }
+}
@@ -1,11 +1,20 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::io::{self, Read, Write};
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+use std::process::ExitCode;
 
 use serde_json::Value;
 
+#[cfg(feature = "highlight")]
+use mdbook_rss_feed::{DEFAULT_HIGHLIGHT_THEME, highlight_themes};
+
 use mdbook_rss_feed::{
-    DefaultBehavior, FeedOptions, articles_from_book_json, build_feed_from_articles,
+    Allowlist, Article, BuildResult, DEFAULT_EXCERPT_SEPARATOR, DateSource, DefaultBehavior,
+    Diagnostics, FeedOptions, FormatRegistry, GitHistory, MathMode, PathFilter, Person,
+    PreviewOptions, PreviewUnit, RssFormat, Severity, articles_from_book_json,
+    build_feed_from_articles, collect_articles, select_articles, strip_excerpt_separators,
+    validate_feed,
 };
 
 fn handle_mdbook_hooks(args: &[String]) -> bool {
@@ -25,13 +34,386 @@ fn handle_mdbook_hooks(args: &[String]) -> bool {
     false
 }
 
+/// Look up an `rss-feed` setting.
+///
+/// `[preprocessor.rss-feed]` wins; `[output.rss-feed]` is the fallback so the
+/// backend can be configured on its own, without the preprocessor.
+fn setting<'a>(context: &'a Value, key: &str) -> Option<&'a Value> {
+    context
+        .pointer(&format!("/config/preprocessor/rss-feed/{key}"))
+        .or_else(|| context.pointer(&format!("/config/output/rss-feed/{key}")))
+}
+
+/// A boolean [`setting`], `false` when unset.
+fn flag(context: &Value, key: &str) -> bool {
+    setting(context, key)
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// File name stems of the main feed's pages (`rss.xml`, `atom.xml`,
+/// `feed.json`), which a section at the feed root must not reuse.
+const MAIN_FEED_STEMS: [&str; 3] = ["rss", "atom", "feed"];
+
+/// One `[preprocessor.rss-feed.feeds.<name>]` table.
+struct SectionConfig {
+    name: String,
+    filter: PathFilter,
+    title: String,
+    description: String,
+    /// Directory part of `output`, relative to the feed root.
+    output_dir: String,
+    /// File name stem of `output`, defaults to `name`.
+    output_stem: String,
+}
+
+impl SectionConfig {
+    /// Parse every named feed table, skipping (or, in strict mode, failing on)
+    /// tables without a `path` or `glob` selector, with an `output` outside
+    /// the feed root, or with an `output` whose files another feed writes.
+    fn all_from_json(context: &Value, book_title: &str, book_description: &str) -> Vec<Self> {
+        let strict = flag(context, "strict");
+        let Some(tables) = setting(context, "feeds").and_then(Value::as_object) else {
+            return Vec::new();
+        };
+
+        let mut sections = Vec::new();
+        let mut outputs = BTreeMap::new();
+        for (name, table) in tables {
+            let get = |key: &str| table.get(key).and_then(Value::as_str);
+            let output = get("output").unwrap_or(name).trim_end_matches('/');
+            let (output_dir, output_stem) = output.rsplit_once('/').unwrap_or(("", output));
+            let filter = match (get("path"), get("glob")) {
+                _ if !is_relative_output(output) => Err(format!(
+                    "has an invalid `output` `{output}`, expected a relative path \
+                     without `.` or `..`"
+                )),
+                _ if output_dir.is_empty() && MAIN_FEED_STEMS.contains(&output_stem) => Err(
+                    format!("has an `output` `{output}` that clashes with the main feed"),
+                ),
+                _ if outputs.contains_key(&(output_dir, output_stem)) => Err(format!(
+                    "has the same `output` `{output}` as feed `{}`",
+                    outputs[&(output_dir, output_stem)]
+                )),
+                (_, Some(glob)) => Ok(PathFilter::Glob(glob.to_string())),
+                (Some(prefix), None) => Ok(PathFilter::Prefix(prefix.to_string())),
+                (None, None) => Err("needs a `path` or `glob` selector".to_string()),
+            };
+            let filter = match filter {
+                Ok(filter) => filter,
+                Err(problem) => {
+                    let msg = format!("mdbook-rss-feed: feed `{name}` {problem}");
+                    if strict {
+                        eprintln!("error: {msg}");
+                        std::process::exit(1);
+                    }
+                    eprintln!("warning: {msg}, skipping it");
+                    continue;
+                }
+            };
+            outputs.insert((output_dir, output_stem), name);
+            sections.push(Self {
+                name: name.clone(),
+                filter,
+                title: get("title")
+                    .map_or_else(|| format!("{book_title} - {name}"), str::to_string),
+                description: get("description").unwrap_or(book_description).to_string(),
+                output_dir: output_dir.to_string(),
+                output_stem: output_stem.to_string(),
+            });
+        }
+        sections
+    }
+}
+
+/// Whether a section `output` stays inside the feed root: relative, and made
+/// of plain names only.
+fn is_relative_output(output: &str) -> bool {
+    !output.is_empty()
+        && Path::new(output)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Parse the `[preprocessor.rss-feed.authors.<id>]` tables. `name` defaults
+/// to the id; an invalid table is skipped with a warning, or fails the build
+/// under `strict`.
+fn author_registry(context: &Value) -> BTreeMap<String, Person> {
+    let strict = flag(context, "strict");
+    let Some(tables) = setting(context, "authors").and_then(Value::as_object) else {
+        return BTreeMap::new();
+    };
+
+    let mut registry = BTreeMap::new();
+    for (id, table) in tables {
+        match serde_json::from_value::<Person>(table.clone()) {
+            Ok(mut person) => {
+                if person.name.trim().is_empty() {
+                    person.name.clone_from(id);
+                }
+                registry.insert(id.clone(), person);
+            }
+            Err(e) => {
+                let msg = format!("mdbook-rss-feed: invalid author `{id}`: {e}");
+                if strict {
+                    eprintln!("error: {msg}");
+                    std::process::exit(1);
+                }
+                eprintln!("warning: {msg}, skipping it");
+            }
+        }
+    }
+    registry
+}
+
+/// The sanitizer allowlist: the library defaults, with `allowed-tags` and
+/// `allowed-attributes` replacing either list, MathML added for
+/// `math = "mathml"`, the classes of math rendered as code kept with any
+/// `math` mode, and highlighting styles kept with `highlight = true`.
+/// `None` with `sanitize = false`.
+fn allowlist(context: &Value, preview: &PreviewConfig) -> Option<Allowlist> {
+    if setting(context, "sanitize").and_then(Value::as_bool) == Some(false) {
+        return None;
+    }
+    let names = |key: &str| {
+        setting(context, key).and_then(Value::as_array).map(|arr| {
+            arr.iter()
+                .filter_map(Value::as_str)
+                .map(|name| name.trim().to_ascii_lowercase())
+                .collect::<BTreeSet<_>>()
+        })
+    };
+    let mut allowlist = Allowlist::default();
+    if let Some(tags) = names("allowed-tags") {
+        allowlist.tags = tags;
+    }
+    if let Some(attributes) = names("allowed-attributes") {
+        allowlist.attributes = attributes;
+    }
+    if preview.math == Some(MathMode::Mathml) {
+        allowlist = allowlist.with_mathml();
+    }
+    if preview.math.is_some() {
+        allowlist = allowlist.with_math_classes();
+    }
+    if preview.highlight.is_some() {
+        allowlist = allowlist.with_highlight_styles();
+    }
+    Some(allowlist)
+}
+
+/// The `highlight-theme` to use with `highlight = true`; an unknown theme
+/// falls back to the default with a warning.
+#[cfg(feature = "highlight")]
+fn highlight_theme(context: &Value) -> Option<String> {
+    if !flag(context, "highlight") {
+        return None;
+    }
+    let theme = setting(context, "highlight-theme")
+        .and_then(Value::as_str)
+        .unwrap_or(DEFAULT_HIGHLIGHT_THEME);
+    let themes = highlight_themes();
+    if themes.contains(&theme) {
+        return Some(theme.to_string());
+    }
+    eprintln!(
+        "warning: mdbook-rss-feed: unknown highlight-theme `{theme}`, expected one of \"{}\", \
+         falling back to \"{DEFAULT_HIGHLIGHT_THEME}\"",
+        themes.join("\", \"")
+    );
+    Some(DEFAULT_HIGHLIGHT_THEME.to_string())
+}
+
+#[cfg(not(feature = "highlight"))]
+fn highlight_theme(context: &Value) -> Option<String> {
+    if flag(context, "highlight") {
+        eprintln!(
+            "warning: mdbook-rss-feed: `highlight = true` is set but this binary was \
+             compiled without the `highlight` feature. Reinstall with: \
+             cargo install mdbook-rss-feed --features highlight"
+        );
+    }
+    None
+}
+
+/// `math`: how TeX math is rendered, or `None` to leave it as written.
+/// Without the `mathml` feature, `"mathml"` falls back to `"code"`.
+fn math_mode(context: &Value) -> Option<MathMode> {
+    let mode = setting(context, "math")
+        .and_then(Value::as_str)
+        .map(str::parse::<MathMode>)
+        .transpose()
+        .unwrap_or_else(|e| {
+            eprintln!("warning: mdbook-rss-feed: {e}, leaving math as written");
+            None
+        })?;
+    if mode == MathMode::Mathml && !cfg!(feature = "mathml") {
+        eprintln!(
+            "warning: mdbook-rss-feed: `math = \"mathml\"` is set but this binary was \
+             compiled without the `mathml` feature, falling back to \"code\". Reinstall \
+             with: cargo install mdbook-rss-feed --features mathml"
+        );
+        return Some(MathMode::Code);
+    }
+    Some(mode)
+}
+
+/// The preview settings: excerpt marker, sizing and how a cut is marked.
+struct PreviewConfig {
+    /// `excerpt-separator`; `None` when set to `""`.
+    excerpt_separator: Option<String>,
+    /// `preview-paragraphs`, `preview-max-chars`, `preview-min-body-chars`
+    /// and `preview-unit`; the library defaults when unset.
+    max_paragraphs: Option<usize>,
+    max_length: Option<usize>,
+    min_body_chars: Option<usize>,
+    unit: PreviewUnit,
+    /// `preview-ellipsis`, appended where a preview is cut mid-paragraph.
+    ellipsis: Option<String>,
+    /// `read-more`: text of a link to the chapter after shortened previews.
+    read_more: Option<String>,
+    /// Theme for `highlight = true`, see [`highlight_theme`].
+    highlight: Option<String>,
+    /// `math`, see [`math_mode`].
+    math: Option<MathMode>,
+}
+
+impl PreviewConfig {
+    fn from_json(context: &Value) -> Self {
+        let text = |key: &str| setting(context, key).and_then(Value::as_str);
+        let count = |key: &str| {
+            setting(context, key)
+                .and_then(Value::as_u64)
+                .map(|n| usize::try_from(n).unwrap_or(usize::MAX))
+        };
+        let unit = text("preview-unit")
+            .map(str::parse::<PreviewUnit>)
+            .transpose()
+            .unwrap_or_else(|e| {
+                eprintln!("warning: mdbook-rss-feed: {e}, falling back to \"chars\"");
+                Some(PreviewUnit::Chars)
+            })
+            .unwrap_or_default();
+
+        Self {
+            excerpt_separator: text("excerpt-separator")
+                .map_or(Some(DEFAULT_EXCERPT_SEPARATOR), |s| {
+                    Some(s.trim()).filter(|s| !s.is_empty())
+                })
+                .map(str::to_string),
+            max_paragraphs: count("preview-paragraphs"),
+            max_length: count("preview-max-chars"),
+            min_body_chars: count("preview-min-body-chars"),
+            unit,
+            ellipsis: text("preview-ellipsis").map(str::to_string),
+            read_more: text("read-more").map(str::to_string),
+            highlight: highlight_theme(context),
+            math: math_mode(context),
+        }
+    }
+
+    fn options(&self) -> PreviewOptions<'_> {
+        let defaults = PreviewOptions::default();
+        PreviewOptions {
+            excerpt_separator: self.excerpt_separator.as_deref(),
+            max_paragraphs: self.max_paragraphs.unwrap_or(defaults.max_paragraphs),
+            max_length: self.max_length.unwrap_or(defaults.max_length),
+            unit: self.unit,
+            min_body_chars: self.min_body_chars.unwrap_or(defaults.min_body_chars),
+            ellipsis: self.ellipsis.as_deref().unwrap_or(defaults.ellipsis),
+            read_more: self.read_more.as_deref(),
+            highlight: self.highlight.as_deref(),
+            math: self.math,
+        }
+    }
+}
+
+/// Where the generated feed files are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum OutputMode {
+    /// Legacy: the preprocessor writes into the book's `src/` so mdBook
+    /// copies them to the output. Dirties the source tree on every build.
+    /// The `[output.rss-feed]` renderer, if configured, writes into its own
+    /// `destination`.
+    #[default]
+    Src,
+    /// Leave `src/` alone. Feeds are written into the HTML output directory
+    /// by the `[output.rss-feed]` renderer once the HTML renderer has run.
+    Build,
+}
+
+impl std::str::FromStr for OutputMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "src" => Ok(Self::Src),
+            "build" => Ok(Self::Build),
+            other => Err(format!(
+                "unknown output-mode `{other}`, expected \"src\" or \"build\""
+            )),
+        }
+    }
+}
+
+/// Where undated chapters get their dates from (`date-source`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum DateSourceKind {
+    /// File modification time; nothing in preprocessor and backend modes.
+    #[default]
+    Mtime,
+    /// First and last commit touching the chapter, from the local repository.
+    Git,
+}
+
+impl std::str::FromStr for DateSourceKind {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "mtime" => Ok(Self::Mtime),
+            "git" => Ok(Self::Git),
+            other => Err(format!(
+                "unknown date-source `{other}`, expected \"mtime\" or \"git\""
+            )),
+        }
+    }
+}
+
+/// Directory the HTML renderer writes to, mirroring mdBook's own rule: the
+/// build dir itself for a single backend, `<build-dir>/html` when several
+/// `[output.*]` tables are configured.
+fn html_output_dir(context: &Value, root: &Path) -> PathBuf {
+    let build_dir = root.join(
+        context
+            .pointer("/config/build/build-dir")
+            .and_then(Value::as_str)
+            .unwrap_or("book"),
+    );
+    let backends = context
+        .pointer("/config/output")
+        .and_then(Value::as_object)
+        .map_or(1, serde_json::Map::len);
+    if backends > 1 {
+        build_dir.join("html")
+    } else {
+        build_dir
+    }
+}
+
 #[allow(clippy::struct_excessive_bools)]
 struct FeedConfig {
     src_dir: PathBuf,
+    /// HTML output directory the `rss-feed` backend writes into.
+    html_dir: PathBuf,
+    output_mode: OutputMode,
+    date_source: DateSourceKind,
     site_url: String,
     title: String,
     description: String,
     full_preview: bool,
+    full_content: bool,
+    preview: PreviewConfig,
+    /// `sanitize`, `allowed-tags` and `allowed-attributes`, see [`allowlist`].
+    sanitize: Option<Allowlist>,
     paginated: bool,
     max_items: usize,
     default_behavior: DefaultBehavior,
@@ -42,7 +424,15 @@ struct FeedConfig {
     #[cfg_attr(not(feature = "atom"), allow(dead_code))]
     authors: Vec<String>,
     strict: bool,
+    /// Run [`validate_feed`] on every generated file before writing it.
+    validate: bool,
     author_email: Option<String>,
+    /// `[preprocessor.rss-feed.authors.<id>]` tables, keyed by id.
+    author_registry: BTreeMap<String, Person>,
+    tag_feeds: bool,
+    sections: Vec<SectionConfig>,
+    /// Where to write the JSON diagnostics report, relative to the book root.
+    diagnostics_file: Option<PathBuf>,
 }
 
 impl FeedConfig {
@@ -52,50 +442,71 @@ impl FeedConfig {
             .and_then(|v| v.as_str())
             .unwrap_or(".");
 
-        let default_behavior = context
-            .pointer("/config/preprocessor/rss-feed/default-behavior")
+        let default_behavior = setting(context, "default-behavior")
             .and_then(Value::as_str)
             .and_then(|s| s.parse::<DefaultBehavior>().ok())
             .unwrap_or_default();
 
+        let title = context
+            .pointer("/config/book/title")
+            .and_then(|v| v.as_str())
+            .unwrap_or("My mdBook")
+            .to_string();
+        let description = context
+            .pointer("/config/book/description")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Description")
+            .to_string();
+        let sections = SectionConfig::all_from_json(context, &title, &description);
+
+        let output_mode = setting(context, "output-mode")
+            .and_then(Value::as_str)
+            .map(str::parse::<OutputMode>)
+            .transpose()
+            .unwrap_or_else(|e| {
+                eprintln!("warning: mdbook-rss-feed: {e}, falling back to \"src\"");
+                Some(OutputMode::Src)
+            })
+            .unwrap_or_default();
+        let date_source = setting(context, "date-source")
+            .and_then(Value::as_str)
+            .map(str::parse::<DateSourceKind>)
+            .transpose()
+            .unwrap_or_else(|e| {
+                eprintln!("warning: mdbook-rss-feed: {e}, falling back to \"mtime\"");
+                Some(DateSourceKind::Mtime)
+            })
+            .unwrap_or_default();
+
+        let preview = PreviewConfig::from_json(context);
         Self {
-            src_dir: PathBuf::from(root).join("src"),
+            src_dir: PathBuf::from(root).join(
+                context
+                    .pointer("/config/book/src")
+                    .and_then(Value::as_str)
+                    .unwrap_or("src"),
+            ),
+            html_dir: html_output_dir(context, Path::new(root)),
+            output_mode,
+            date_source,
             site_url: context
                 .pointer("/config/output/html/site-url")
                 .and_then(|v| v.as_str())
                 .unwrap_or("https://example.com/")
                 .to_string(),
-            title: context
-                .pointer("/config/book/title")
-                .and_then(|v| v.as_str())
-                .unwrap_or("My mdBook")
-                .to_string(),
-            description: context
-                .pointer("/config/book/description")
-                .and_then(|v| v.as_str())
-                .unwrap_or("Description")
-                .to_string(),
-            full_preview: context
-                .pointer("/config/preprocessor/rss-feed/full-preview")
-                .and_then(Value::as_bool)
-                .unwrap_or(false),
-            paginated: context
-                .pointer("/config/preprocessor/rss-feed/paginated")
-                .and_then(Value::as_bool)
-                .unwrap_or(false),
-            max_items: context
-                .pointer("/config/preprocessor/rss-feed/max-items")
+            title,
+            description,
+            full_preview: flag(context, "full-preview"),
+            full_content: flag(context, "full-content"),
+            sanitize: allowlist(context, &preview),
+            preview,
+            paginated: flag(context, "paginated"),
+            max_items: setting(context, "max-items")
                 .and_then(Value::as_u64)
                 .map_or(0, |n| usize::try_from(n).unwrap_or(usize::MAX)),
             default_behavior,
-            json_enabled: context
-                .pointer("/config/preprocessor/rss-feed/json-feed")
-                .and_then(Value::as_bool)
-                .unwrap_or(false),
-            atom_enabled: context
-                .pointer("/config/preprocessor/rss-feed/atom")
-                .and_then(Value::as_bool)
-                .unwrap_or(false),
+            json_enabled: flag(context, "json-feed"),
+            atom_enabled: flag(context, "atom"),
             authors: context
                 .pointer("/config/book/authors")
                 .and_then(Value::as_array)
@@ -106,92 +517,123 @@ impl FeedConfig {
                         .collect()
                 })
                 .unwrap_or_default(),
-            strict: context
-                .pointer("/config/preprocessor/rss-feed/strict")
-                .and_then(Value::as_bool)
-                .unwrap_or(false),
-            author_email: context
-                .pointer("/config/preprocessor/rss-feed/author-email")
+            strict: flag(context, "strict"),
+            validate: flag(context, "validate"),
+            author_email: setting(context, "author-email")
                 .and_then(Value::as_str)
                 .map(str::to_string),
+            author_registry: author_registry(context),
+            tag_feeds: flag(context, "tag-feeds"),
+            sections,
+            diagnostics_file: setting(context, "diagnostics-file")
+                .and_then(Value::as_str)
+                .map(|file| Path::new(root).join(file)),
         }
     }
     fn feed_options(&self) -> FeedOptions<'_> {
         FeedOptions {
             title: &self.title,
-            site_url: &self.site_url,
             description: &self.description,
             full_preview: self.full_preview,
+            full_content: self.full_content,
+            preview: self.preview.options(),
+            sanitize: self.sanitize.as_ref(),
             max_items: self.max_items,
             paginated: self.paginated,
             default_behavior: self.default_behavior.clone(),
             strict: self.strict,
             author_email: self.author_email.clone(),
+            author_registry: self.author_registry.clone(),
+            tag_feeds: self.tag_feeds,
+            ..FeedOptions::new(&self.site_url)
+        }
+    }
+
+    /// Load the configured [`DateSource`]. Git history that can't be read
+    /// (no repository, no `git` binary) falls back to mtime with a warning,
+    /// or fails the build under `strict`.
+    fn load_date_source(&self) -> Result<DateSource, Box<dyn std::error::Error>> {
+        if self.date_source == DateSourceKind::Mtime {
+            return Ok(DateSource::Mtime);
+        }
+        match GitHistory::load(&self.src_dir) {
+            Ok(history) => {
+                if history.is_shallow() {
+                    eprintln!(
+                        "warning: mdbook-rss-feed: shallow git clone, publish dates \
+                         come from the oldest fetched commit (fetch full history, \
+                         e.g. `fetch-depth: 0`)"
+                    );
+                }
+                Ok(DateSource::Git(history))
+            }
+            Err(e) if self.strict => Err(e.into()),
+            Err(e) => {
+                eprintln!("warning: mdbook-rss-feed: {e}, falling back to \"mtime\"");
+                Ok(DateSource::Mtime)
+            }
+        }
+    }
+
+    /// Options for a section feed: the book-level settings with the
+    /// section's own title, description and output file names.
+    fn section_options<'a>(&'a self, section: &'a SectionConfig) -> FeedOptions<'a> {
+        FeedOptions {
+            title: &section.title,
+            description: &section.description,
+            output_dir: &section.output_dir,
+            output_stem: Some(&section.output_stem),
+            ..self.feed_options()
         }
     }
 }
 
-fn write_rss_pages(config: &FeedConfig, pages: &[mdbook_rss_feed::FeedPage]) -> io::Result<()> {
-    for page in pages {
-        let rss_path = config.src_dir.join(&page.filename);
-        let rss_content = page.channel.to_string();
-        eprintln!(
-            "Writing RSS page {} ({} bytes)",
-            rss_path.display(),
-            rss_content.len()
-        );
-        fs::write(&rss_path, &rss_content)?;
+/// Write `contents` to `path`, creating parent directories for topic feeds.
+fn write_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
     }
-    Ok(())
+    fs::write(path, contents)
 }
 
-#[cfg(feature = "json-feed")]
-fn write_json_pages(
+/// Validate a generated feed before it is written, when `validate = true`.
+///
+/// Every issue is printed; under `strict` an error-level issue aborts the
+/// build instead of publishing a feed readers may reject.
+fn check_generated(
     config: &FeedConfig,
-    pages: &[mdbook_rss_feed::FeedPage],
+    path: &Path,
+    self_url: &str,
+    content: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use mdbook_rss_feed::rss_to_json_feed;
-
-    if !config.json_enabled {
+    if !config.validate {
         return Ok(());
     }
-    let base = config.site_url.trim_end_matches('/');
-    let total = pages.len();
-    for (page_idx, page) in pages.iter().enumerate() {
-        let suffix = if page_idx == 0 {
-            String::new()
-        } else {
-            (page_idx + 1).to_string()
-        };
-        let self_url = format!("{base}/feed{suffix}.json");
-
-        let next_url = if page_idx + 1 < total {
-            Some(format!("{base}/feed{}.json", page_idx + 2))
-        } else {
-            None
-        };
-        let json_feed = rss_to_json_feed(&page.channel, Some(&self_url), next_url.as_deref());
-        let json_path = config.src_dir.join(if page_idx == 0 {
-            "feed.json".to_string()
-        } else {
-            format!("feed{}.json", page_idx + 1)
-        });
-        fs::write(&json_path, serde_json::to_vec_pretty(&json_feed)?)?;
-        let json_bytes = serde_json::to_vec_pretty(&json_feed)?;
-        eprintln!(
-            "Writing JSON page {} ({} bytes)",
-            json_path.display(),
-            json_bytes.len()
-        );
+    let report = validate_feed(content, Some(self_url));
+    for issue in &report.issues {
+        eprintln!("mdbook-rss-feed: {}: {issue}", path.display());
+    }
+    if config.strict && report.has_errors() {
+        return Err(format!(
+            "{} failed validation with {} error(s)",
+            path.display(),
+            report.count(Severity::Error)
+        )
+        .into());
     }
     Ok(())
 }
 
+/// Register JSON Feed when `json-feed = true`.
+#[cfg(feature = "json-feed")]
+fn register_json(config: &FeedConfig, registry: &mut FormatRegistry) {
+    if config.json_enabled {
+        registry.register(mdbook_rss_feed::JsonFeedFormat);
+    }
+}
+
 #[cfg(not(feature = "json-feed"))]
-fn write_json_pages(
-    config: &FeedConfig,
-    _pages: &[mdbook_rss_feed::FeedPage],
-) -> Result<(), Box<dyn std::error::Error>> {
+fn register_json(config: &FeedConfig, _registry: &mut FormatRegistry) {
     if config.json_enabled {
         let msg = "mdbook-rss-feed: `json-feed = true` is set but this binary was \
                    compiled without the `json-feed` feature. Reinstall with: \
@@ -202,116 +644,333 @@ fn write_json_pages(
         }
         eprintln!("warning: {msg}");
     }
-    Ok(())
 }
 
+/// Register Atom when `atom = true`.
 #[cfg(feature = "atom")]
-fn write_atom_pages(config: &FeedConfig, pages: &[mdbook_rss_feed::FeedPage]) -> io::Result<()> {
-    use mdbook_rss_feed::rss_to_atom;
-
-    if !config.atom_enabled {
-        return Ok(());
-    }
-    let base = config.site_url.trim_end_matches('/');
-    let total = pages.len();
-    for (page_idx, page) in pages.iter().enumerate() {
-        let suffix = if page_idx == 0 {
-            String::new()
-        } else {
-            (page_idx + 1).to_string()
-        };
-        let self_url = format!("{base}/atom{suffix}.xml");
-        // next points to older page, prev points to newer page.
-        let next_url = if page_idx + 1 < total {
-            Some(format!("{base}/atom{}.xml", page_idx + 2))
-        } else {
-            None
-        };
-        let prev_url = if page_idx > 0 {
-            let prev_suffix = if page_idx == 1 {
-                String::new()
-            } else {
-                page_idx.to_string()
-            };
-            Some(format!("{base}/atom{prev_suffix}.xml"))
-        } else {
-            None
-        };
-        let atom_feed = rss_to_atom(
-            &page.channel,
-            Some(&self_url),
-            next_url.as_deref(),
-            prev_url.as_deref(),
-            &config.authors,
-        );
-        let atom_path = config.src_dir.join(if page_idx == 0 {
-            "atom.xml".to_string()
-        } else {
-            format!("atom{}.xml", page_idx + 1)
+fn register_atom(config: &FeedConfig, registry: &mut FormatRegistry) {
+    if config.atom_enabled {
+        registry.register(mdbook_rss_feed::AtomFormat {
+            authors: config.authors.clone(),
         });
-        fs::write(&atom_path, atom_feed.to_string())?;
-        eprintln!(
-            "Writing Atom page {} ({} bytes)",
-            atom_path.display(),
-            atom_feed.to_string().len()
-        );
     }
-    Ok(())
 }
 
 #[cfg(not(feature = "atom"))]
-fn write_atom_pages(_config: &FeedConfig, _pages: &[mdbook_rss_feed::FeedPage]) -> io::Result<()> {
-    if _config.atom_enabled {
+fn register_atom(config: &FeedConfig, _registry: &mut FormatRegistry) {
+    if config.atom_enabled {
         let msg = "mdbook-rss-feed: `atom = true` is set but this binary was \
                    compiled without the `atom` feature. Reinstall with: \
                    cargo install mdbook-rss-feed --features atom";
-        if _config.strict {
+        if config.strict {
             eprintln!("error: {msg}");
             std::process::exit(1);
         }
         eprintln!("warning: {msg}");
     }
-    Ok(())
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = std::env::args().collect();
-    if handle_mdbook_hooks(&args) {
-        return Ok(());
-    }
-
-    // 1. READ STDIN
-    let mut input = String::new();
-    io::stdin().read_to_string(&mut input)?;
-
-    // 2. PARSE JSON
-    let input_array: Vec<Value> = serde_json::from_str(&input)?;
-    let [context, book] = input_array.as_slice() else {
-        return Err("expected mdBook to send a [context, book] pair on stdin".into());
-    };
-
-    // 3. EXTRACT CONFIG & BOOK
-    let config = FeedConfig::from_json(context);
+/// The formats to write: RSS always, then JSON Feed and Atom when enabled.
+fn formats(config: &FeedConfig) -> FormatRegistry {
+    let mut registry = FormatRegistry::new();
+    registry.register(RssFormat);
+    register_json(config, &mut registry);
+    register_atom(config, &mut registry);
+    registry
+}
 
-    // 4. COLLECT ARTICLES FROM THE BOOK JSON
-    // This uses the already-processed book rather than walking the fs
-    let articles = articles_from_book_json(book, config.strict);
+/// Render every page of `result` in every registered format and write it
+/// under `out_dir`.
+fn write_pages(
+    config: &FeedConfig,
+    registry: &FormatRegistry,
+    out_dir: &Path,
+    result: &BuildResult,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for page in registry.render(result, &config.site_url)? {
+        let path = out_dir.join(&page.path);
+        check_generated(config, &path, &page.self_url, &page.content)?;
+        write_file(&path, page.content.as_bytes())?;
+        eprintln!(
+            "Writing {} page {} ({} bytes)",
+            page.format,
+            path.display(),
+            page.content.len()
+        );
+    }
+    Ok(())
+}
 
+/// Build every configured feed from `articles` and write them under
+/// `out_dir`.
+fn build_and_write(
+    config: &FeedConfig,
+    articles: Vec<Article>,
+    out_dir: &Path,
+    diagnostics: &mut Diagnostics,
+) -> Result<(), Box<dyn std::error::Error>> {
     eprintln!(
         "mdbook-rss-feed: collected {} chapter(s) from book (default-behavior: {:?})",
         articles.len(),
         config.default_behavior,
     );
 
-    // 5. BUILD FEED
-    let result = build_feed_from_articles(articles, &config.feed_options());
+    let mut results = Vec::with_capacity(config.sections.len() + 1);
+    for section in &config.sections {
+        let selected = select_articles(&articles, &section.filter);
+        eprintln!(
+            "mdbook-rss-feed: feed `{}` selected {} chapter(s)",
+            section.name,
+            selected.len()
+        );
+        results.push(build_feed_from_articles(
+            selected,
+            &config.section_options(section),
+            diagnostics,
+        ));
+    }
+    results.insert(
+        0,
+        build_feed_from_articles(articles, &config.feed_options(), diagnostics),
+    );
+
+    let registry = formats(config);
+    for feed in results
+        .iter()
+        .flat_map(|r| std::iter::once(r).chain(&r.tag_feeds))
+    {
+        write_pages(config, &registry, out_dir, feed)?;
+    }
+    Ok(())
+}
+
+/// Print the per-chapter warnings collected during the run, and write them
+/// as JSON when `diagnostics-file` is set.
+fn report_diagnostics(
+    config: &FeedConfig,
+    diagnostics: &Diagnostics,
+) -> Result<(), Box<dyn std::error::Error>> {
+    eprint!("{}", diagnostics.summary());
+    if let Some(path) = &config.diagnostics_file {
+        write_file(path, &serde_json::to_vec_pretty(&diagnostics.to_json())?)?;
+        eprintln!("mdbook-rss-feed: wrote diagnostics to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Preprocessor protocol: `[context, book]` on stdin, book echoed on stdout.
+fn run_preprocessor(context: &Value, book: &Value) -> Result<(), Box<dyn std::error::Error>> {
+    let config = FeedConfig::from_json(context);
 
-    write_rss_pages(&config, &result.pages)?;
-    write_json_pages(&config, &result.pages)?;
-    write_atom_pages(&config, &result.pages)?;
+    match config.output_mode {
+        OutputMode::Src if context.pointer("/config/output/rss-feed").is_some() => {
+            // The `rss-feed` backend writes the same feeds into its own
+            // destination; writing them to `src/` as well would publish
+            // them twice.
+            eprintln!(
+                "mdbook-rss-feed: the `rss-feed` backend writes the feeds, \
+                 not writing them to src/"
+            );
+        }
+        OutputMode::Src => {
+            // This uses the already-processed book rather than walking the fs
+            let dates = config.load_date_source()?;
+            let mut diagnostics = Diagnostics::new();
+            let articles = articles_from_book_json(book, config.strict, &dates, &mut diagnostics)?;
+            build_and_write(&config, articles, &config.src_dir, &mut diagnostics)?;
+            report_diagnostics(&config, &diagnostics)?;
+        }
+        OutputMode::Build => {
+            // mdBook's HTML renderer empties its output directory before it
+            // renders, so anything written there now would be deleted. The
+            // `rss-feed` backend writes the feeds after rendering instead.
+            if context.pointer("/config/output/rss-feed").is_none() {
+                eprintln!(
+                    "error: mdbook-rss-feed: `output-mode = \"build\"` needs an \
+                     `[output.rss-feed]` table in book.toml to write the feeds"
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // The marker is only meant for feeds. The `rss-feed` backend still needs
+    // it when this preprocessor runs for it, so only strip it for the others.
+    let mut book = book.clone();
+    if let Some(separator) = &config.preview.excerpt_separator
+        && context.get("renderer").and_then(Value::as_str) != Some("rss-feed")
+    {
+        strip_excerpt_separators(&mut book, separator);
+    }
 
-    // 6. FINAL ECHO TO MDBOOK
+    // FINAL ECHO TO MDBOOK
     io::stderr().flush()?;
-    println!("{}", serde_json::to_string(book)?);
+    println!("{}", serde_json::to_string(&book)?);
+    Ok(())
+}
+
+/// Renderer protocol: mdBook's `RenderContext` on stdin, nothing on stdout.
+///
+/// Feeds are written into the backend's own `destination`, or next to the
+/// rendered HTML with `output-mode = "build"`. Neither the book nor `src/`
+/// is modified.
+fn run_backend(render_context: &Value) -> Result<(), Box<dyn std::error::Error>> {
+    let config = FeedConfig::from_json(render_context);
+    let book = render_context
+        .get("book")
+        .ok_or("mdBook render context has no `book`")?;
+    let out_dir = match config.output_mode {
+        OutputMode::Build => config.html_dir.clone(),
+        OutputMode::Src => render_context
+            .get("destination")
+            .and_then(Value::as_str)
+            .map(PathBuf::from)
+            .ok_or("mdBook render context has no `destination`")?,
+    };
+    let dates = config.load_date_source()?;
+    let mut diagnostics = Diagnostics::new();
+    let articles = articles_from_book_json(book, config.strict, &dates, &mut diagnostics)?;
+    build_and_write(&config, articles, &out_dir, &mut diagnostics)?;
+    report_diagnostics(&config, &diagnostics)
+}
+
+const BUILD_USAGE: &str = "usage: mdbook-rss-feed build [--book <dir>] [--out <dir>]";
+
+/// Standalone CLI: `mdbook-rss-feed build --book <dir> [--out <dir>]`.
+///
+/// Reads `<dir>/book.toml` itself and walks the `src` directory with
+/// [`collect_articles`], so no mdBook run is needed. Without `--out`, files
+/// go where `output-mode` would put them (`src/` or the HTML build dir).
+fn run_build(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut book_dir = PathBuf::from(".");
+    let mut out_dir: Option<PathBuf> = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .map(PathBuf::from)
+                .ok_or_else(|| format!("`{arg}` needs a directory ({BUILD_USAGE})"))
+        };
+        match arg.as_str() {
+            "--book" => book_dir = value()?,
+            "--out" => out_dir = Some(value()?),
+            "-h" | "--help" => {
+                println!("{BUILD_USAGE}");
+                return Ok(());
+            }
+            other => return Err(format!("unknown argument `{other}` ({BUILD_USAGE})").into()),
+        }
+    }
+
+    let book_toml = book_dir.join("book.toml");
+    let raw = fs::read_to_string(&book_toml)
+        .map_err(|e| format!("failed to read {}: {e}", book_toml.display()))?;
+    let book_config: toml::Table = toml::from_str(&raw)
+        .map_err(|e| format!("failed to parse {}: {e}", book_toml.display()))?;
+
+    // Same shape as the context mdBook hands to preprocessors, so the config
+    // parsing is shared with the other modes.
+    let context = serde_json::json!({
+        "root": book_dir,
+        "config": serde_json::to_value(book_config)?,
+    });
+    let config = FeedConfig::from_json(&context);
+
+    let dates = config.load_date_source()?;
+    let mut diagnostics = Diagnostics::new();
+    let articles = collect_articles(&config.src_dir, config.strict, &dates, &mut diagnostics)?;
+    let out_dir = out_dir.unwrap_or_else(|| match config.output_mode {
+        OutputMode::Src => config.src_dir.clone(),
+        OutputMode::Build => config.html_dir.clone(),
+    });
+    build_and_write(&config, articles, &out_dir, &mut diagnostics)?;
+    report_diagnostics(&config, &diagnostics)
+}
+
+const VALIDATE_USAGE: &str = "usage: mdbook-rss-feed validate <file> [--url <feed-url>]";
+
+/// `mdbook-rss-feed validate <file> [--url <feed-url>]`.
+///
+/// Prints every issue found in an RSS, Atom or JSON feed file and fails if
+/// any of them is an error. With `--url`, the feed's self link must match it.
+fn run_validate(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file: Option<PathBuf> = None;
+    let mut url: Option<String> = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--url" => {
+                url = Some(
+                    args.next()
+                        .cloned()
+                        .ok_or_else(|| format!("`--url` needs a URL ({VALIDATE_USAGE})"))?,
+                );
+            }
+            "-h" | "--help" => {
+                println!("{VALIDATE_USAGE}");
+                return Ok(());
+            }
+            other if file.is_none() && !other.starts_with('-') => file = Some(PathBuf::from(other)),
+            other => return Err(format!("unexpected argument `{other}` ({VALIDATE_USAGE})").into()),
+        }
+    }
+    let file = file.ok_or_else(|| format!("no feed file given ({VALIDATE_USAGE})"))?;
+
+    let content =
+        fs::read_to_string(&file).map_err(|e| format!("failed to read {}: {e}", file.display()))?;
+    let report = validate_feed(&content, url.as_deref());
+    for issue in &report.issues {
+        println!("{}: {issue}", file.display());
+    }
+    let errors = report.count(Severity::Error);
+    let warnings = report.count(Severity::Warning);
+    if errors > 0 {
+        return Err(format!(
+            "{}: {errors} error(s), {warnings} warning(s)",
+            file.display()
+        )
+        .into());
+    }
+    println!("{}: valid ({warnings} warning(s))", file.display());
     Ok(())
 }
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().collect();
+    if handle_mdbook_hooks(&args) {
+        return Ok(());
+    }
+    if args.get(1).map(String::as_str) == Some("build") {
+        return run_build(&args[2..]);
+    }
+    if args.get(1).map(String::as_str) == Some("validate") {
+        return run_validate(&args[2..]);
+    }
+
+    // 1. READ STDIN
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+
+    // 2. PARSE JSON: preprocessors get a [context, book] pair, backends a
+    // single RenderContext object.
+    let input: Value = serde_json::from_str(&input)?;
+    if let Some([context, book]) = input.as_array().map(Vec::as_slice) {
+        return run_preprocessor(context, book);
+    }
+    if input.get("destination").is_some() {
+        return run_backend(&input);
+    }
+    Err("expected mdBook to send a [context, book] pair or a render context on stdin".into())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: mdbook-rss-feed: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,265 @@
+//! Where chapters without a frontmatter `date` get their dates from.
+//!
+//! Set with `date-source` in `book.toml`:
+//!
+//! - `"mtime"` (default): the file's modification time when chapters are read
+//!   from disk, nothing when they come from mdBook's book JSON.
+//! - `"git"`: the first commit touching the chapter is its publish date, the
+//!   last one its updated date. History is read from the local repository
+//!   with the `git` binary; nothing is fetched.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use chrono::{DateTime, Utc};
+
+use crate::error::{FeedError, Result};
+
+/// Separator emitted before each commit in the `git log` output.
+const COMMIT_MARKER: char = '\u{1e}';
+
+/// Created/updated dates per file, read from `git log`.
+#[derive(Debug, Clone, Default)]
+pub struct GitHistory {
+    /// Path relative to the directory passed to [`GitHistory::load`] →
+    /// (first commit, last commit).
+    dates: HashMap<String, (DateTime<Utc>, DateTime<Utc>)>,
+    shallow: bool,
+}
+
+impl GitHistory {
+    /// Read the history of every file under `dir`.
+    ///
+    /// Paths are keyed relative to `dir`, so pass the book's `src`
+    /// directory and look chapters up by their `source_path`. Renames are
+    /// not followed: a moved chapter starts a new history.
+    ///
+    /// # Errors
+    /// Returns [`FeedError::Git`] if `git` can't be run or `dir` is not
+    /// inside a repository.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let log = run_git(
+            dir,
+            &[
+                "-c",
+                "core.quotePath=false",
+                "log",
+                "--relative",
+                "--no-renames",
+                "--name-only",
+                "--format=%x1e%aI",
+                "--",
+                ".",
+            ],
+        )?;
+        let shallow = run_git(dir, &["rev-parse", "--is-shallow-repository"])?;
+        let mut history = Self::parse_log(&log);
+        history.shallow = shallow.trim() == "true";
+        Ok(history)
+    }
+
+    /// Parse `git log --name-only --format=%x1e%aI` output (newest first).
+    pub(crate) fn parse_log(log: &str) -> Self {
+        let mut dates: HashMap<String, (DateTime<Utc>, DateTime<Utc>)> = HashMap::new();
+        for commit in log.split(COMMIT_MARKER) {
+            let mut lines = commit.lines();
+            let Some(date) = lines
+                .next()
+                .and_then(|d| DateTime::parse_from_rfc3339(d.trim()).ok())
+            else {
+                continue;
+            };
+            let date = date.with_timezone(&Utc);
+            for path in lines.map(str::trim).filter(|l| !l.is_empty()) {
+                dates
+                    .entry(path.to_string())
+                    // Walking backwards in time: every older commit moves
+                    // the creation date, the first one seen is the update.
+                    .and_modify(|(created, _)| *created = date)
+                    .or_insert((date, date));
+            }
+        }
+        Self {
+            dates,
+            shallow: false,
+        }
+    }
+
+    /// Date of the first commit touching `path`.
+    #[must_use]
+    pub fn created(&self, path: &str) -> Option<DateTime<Utc>> {
+        self.dates
+            .get(&normalize(path))
+            .map(|(created, _)| *created)
+    }
+
+    /// Date of the last commit touching `path`.
+    #[must_use]
+    pub fn updated(&self, path: &str) -> Option<DateTime<Utc>> {
+        self.dates
+            .get(&normalize(path))
+            .map(|(_, updated)| *updated)
+    }
+
+    /// `true` for a shallow clone, where every file's "first" commit is just
+    /// the oldest one fetched (CI checkouts default to a depth of 1).
+    #[must_use]
+    pub fn is_shallow(&self) -> bool {
+        self.shallow
+    }
+}
+
+/// Match `git`'s forward-slash paths regardless of how the chapter path was
+/// written.
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/").trim_start_matches("./").to_string()
+}
+
+/// Run `git -C <dir> <args>` and return its stdout.
+fn run_git(dir: &Path, args: &[&str]) -> Result<String> {
+    let git_error = |message: String| FeedError::Git {
+        path: PathBuf::from(dir),
+        message,
+    };
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| git_error(format!("failed to run git: {e}")))?;
+    if !output.status.success() {
+        return Err(git_error(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Fallback for chapters whose frontmatter has no `date`.
+#[derive(Debug, Clone, Default)]
+pub enum DateSource {
+    /// File modification time for chapters read from disk; no date for
+    /// chapters from the book JSON. This is the default.
+    #[default]
+    Mtime,
+    /// First/last commit from a [`GitHistory`] of the `src` directory.
+    Git(GitHistory),
+}
+
+impl DateSource {
+    /// Publish date for the chapter at `path` (relative to `src`), given its
+    /// file modification time when known.
+    #[must_use]
+    pub fn created(&self, path: &str, mtime: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Mtime => mtime,
+            Self::Git(history) => history.created(path),
+        }
+    }
+
+    /// Last-modified date for the chapter at `path`. Only git history
+    /// provides one.
+    #[must_use]
+    pub fn updated(&self, path: &str) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Mtime => None,
+            Self::Git(history) => history.updated(path),
+        }
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const LOG: &str = "\u{1e}2024-03-01T10:00:00+00:00\n\nposts/a.md\nposts/b.md\n\
+                       \u{1e}2024-02-01T10:00:00+01:00\n\nposts/a.md\n\
+                       \u{1e}2024-01-01T10:00:00+00:00\n\nposts/a.md\nREADME.md\n";
+
+    fn date(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn first_commit_is_created_last_is_updated() {
+        let history = GitHistory::parse_log(LOG);
+        assert_eq!(
+            history.created("posts/a.md"),
+            Some(date("2024-01-01T10:00:00Z"))
+        );
+        assert_eq!(
+            history.updated("posts/a.md"),
+            Some(date("2024-03-01T10:00:00Z"))
+        );
+        assert_eq!(history.created("posts/b.md"), history.updated("posts/b.md"));
+        assert_eq!(history.created("missing.md"), None);
+    }
+
+    #[test]
+    fn lookups_normalize_separators() {
+        let history = GitHistory::parse_log(LOG);
+        assert!(history.created("posts\\a.md").is_some());
+        assert!(history.created("./README.md").is_some());
+    }
+
+    #[test]
+    fn mtime_source_has_no_updated_date() {
+        let mtime = Some(date("2024-05-05T00:00:00Z"));
+        assert_eq!(DateSource::Mtime.created("a.md", mtime), mtime);
+        assert_eq!(DateSource::Mtime.updated("a.md"), None);
+        let git = DateSource::Git(GitHistory::parse_log(LOG));
+        assert_eq!(
+            git.created("posts/b.md", mtime),
+            Some(date("2024-03-01T10:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn loads_history_from_a_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str], when: &str| {
+            Command::new("git")
+                .arg("-C")
+                .arg(dir.path())
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .env("GIT_AUTHOR_DATE", when)
+                .env("GIT_COMMITTER_DATE", when)
+                .output()
+        };
+        // Skip quietly where git isn't installed.
+        if git(&["init", "-q"], "").is_err() {
+            return;
+        }
+        let src = dir.path().join("src");
+        fs::create_dir(&src).unwrap();
+        fs::write(src.join("post.md"), "v1").unwrap();
+        git(&["add", "."], "").unwrap();
+        git(&["commit", "-qm", "one"], "2024-01-01T00:00:00Z").unwrap();
+        fs::write(src.join("post.md"), "v2").unwrap();
+        git(&["commit", "-qam", "two"], "2024-06-01T00:00:00Z").unwrap();
+
+        let history = GitHistory::load(&src).unwrap();
+        assert_eq!(
+            history.created("post.md"),
+            Some(date("2024-01-01T00:00:00Z"))
+        );
+        assert_eq!(
+            history.updated("post.md"),
+            Some(date("2024-06-01T00:00:00Z"))
+        );
+        assert!(!history.is_shallow());
+    }
+
+    #[test]
+    fn load_outside_a_repository_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            GitHistory::load(dir.path()),
+            Err(FeedError::Git { .. })
+        ));
+    }
+}
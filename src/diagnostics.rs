@@ -0,0 +1,206 @@
+//! Per-chapter warnings collected while reading chapters and building feeds.
+//!
+//! Problems that don't stop the build (a chapter without a date, an author
+//! that can't be emitted, a preview that came out empty, ...) are recorded in
+//! a [`Diagnostics`] collector instead of being printed on the spot, so the
+//! whole book can be reported at once, either as a readable summary or as
+//! JSON for CI annotations.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// What kind of problem a [`Diagnostic`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiagnosticKind {
+    /// The YAML frontmatter could not be parsed; defaults were used.
+    InvalidFrontmatter,
+    /// The chapter file could not be read and was skipped.
+    UnreadableFile,
+    /// No `date`, so the item has no publish date and sorts last.
+    MissingDate,
+    /// An author has no email, so RSS leaves out its `<author>` element. The
+    /// author is still listed as `<dc:creator>`.
+    MissingAuthorEmail,
+    /// The rendered preview is empty.
+    EmptyPreview,
+    /// Another chapter already produces the same item link.
+    DuplicateLink,
+    /// An `{{#include}}`-style directive names a file that can't be read;
+    /// it was left out of the preview.
+    UnresolvedInclude,
+}
+
+impl DiagnosticKind {
+    /// Stable kebab-case name, as used in the JSON output.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::InvalidFrontmatter => "invalid-frontmatter",
+            Self::UnreadableFile => "unreadable-file",
+            Self::MissingDate => "missing-date",
+            Self::MissingAuthorEmail => "missing-author-email",
+            Self::EmptyPreview => "empty-preview",
+            Self::DuplicateLink => "duplicate-link",
+            Self::UnresolvedInclude => "unresolved-include",
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One warning about one chapter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// Chapter path relative to the `src` directory (e.g. `posts/hello.md`).
+    pub path: String,
+    pub kind: DiagnosticKind,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.path, self.kind, self.message)
+    }
+}
+
+/// Collector threaded through [`crate::collect_articles`],
+/// [`crate::articles_from_book_json`] and [`crate::build_feed_from_articles`].
+///
+/// A chapter that ends up in several feeds (main, section and topic feeds)
+/// is only reported once per problem.
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    entries: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a warning for the chapter at `path`, ignoring exact repeats.
+    pub fn warn(&mut self, path: &str, kind: DiagnosticKind, message: impl Into<String>) {
+        let diagnostic = Diagnostic {
+            path: path.to_string(),
+            kind,
+            message: message.into(),
+        };
+        if !self.entries.contains(&diagnostic) {
+            self.entries.push(diagnostic);
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Recorded diagnostics, in the order they were found.
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.entries.iter()
+    }
+
+    /// Human-readable report grouped by chapter, or an empty string when
+    /// there is nothing to report.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        if self.entries.is_empty() {
+            return String::new();
+        }
+        let mut by_path: BTreeMap<&str, Vec<&Diagnostic>> = BTreeMap::new();
+        for d in &self.entries {
+            by_path.entry(&d.path).or_default().push(d);
+        }
+        // Writing into a `String` cannot fail.
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "mdbook-rss-feed: {} warning(s) in {} chapter(s)",
+            self.entries.len(),
+            by_path.len()
+        );
+        for (path, diagnostics) in by_path {
+            let _ = writeln!(out, "  {path}");
+            for d in diagnostics {
+                let _ = writeln!(out, "    {}: {}", d.kind, d.message);
+            }
+        }
+        out
+    }
+
+    /// Machine-readable report: `{"warnings": N, "diagnostics": [...]}`,
+    /// each entry carrying `path`, `kind` and `message`.
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "warnings": self.entries.len(),
+            "diagnostics": self.entries,
+        })
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_warnings_are_recorded_once() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.warn("a.md", DiagnosticKind::MissingDate, "no date");
+        diagnostics.warn("a.md", DiagnosticKind::MissingDate, "no date");
+        diagnostics.warn("b.md", DiagnosticKind::MissingDate, "no date");
+        assert_eq!(diagnostics.len(), 2);
+    }
+
+    #[test]
+    fn summary_groups_by_chapter() {
+        let mut diagnostics = Diagnostics::new();
+        assert_eq!(diagnostics.summary(), "");
+        diagnostics.warn("b.md", DiagnosticKind::EmptyPreview, "empty");
+        diagnostics.warn("a.md", DiagnosticKind::MissingDate, "no date");
+        diagnostics.warn("b.md", DiagnosticKind::MissingDate, "no date");
+        assert_eq!(
+            diagnostics.summary(),
+            "mdbook-rss-feed: 3 warning(s) in 2 chapter(s)\n  a.md\n    missing-date: no date\n  \
+             b.md\n    empty-preview: empty\n    missing-date: no date\n"
+        );
+    }
+
+    #[test]
+    fn json_uses_kebab_case_kinds() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.warn("a.md", DiagnosticKind::MissingAuthorEmail, "dropped");
+        assert_eq!(
+            diagnostics.to_json(),
+            serde_json::json!({
+                "warnings": 1,
+                "diagnostics": [
+                    {"path": "a.md", "kind": "missing-author-email", "message": "dropped"}
+                ]
+            })
+        );
+    }
+}
@@ -0,0 +1,325 @@
+//! mdBook's `{{#...}}` directives.
+//!
+//! mdBook's `links` preprocessor expands these before a chapter is rendered,
+//! so the book JSON a preprocessor receives no longer has them. Chapters read
+//! from disk (see [`crate::build_feed`]) still do; [`expand_directives`] gives
+//! their previews what mdBook itself would render.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How deep includes may nest, as in mdBook.
+const MAX_INCLUDE_DEPTH: usize = 10;
+
+/// Which lines of an included file to keep.
+#[derive(Debug, PartialEq, Eq)]
+enum Selection<'a> {
+    /// Zero-based `start..end`, either end open.
+    Lines(Option<usize>, Option<usize>),
+    /// Between `ANCHOR: name` and `ANCHOR_END: name`.
+    Anchor(&'a str),
+}
+
+/// Split `file.rs:anchor`, `file.rs:3`, `file.rs:3:`, `file.rs::7` or
+/// `file.rs:3:7` into the path and the lines to take. Line numbers are
+/// 1-based and inclusive, as in mdBook.
+fn parse_include(spec: &str) -> (&str, Selection<'_>) {
+    let Some((path, range)) = spec.split_once(':') else {
+        return (spec, Selection::Lines(None, None));
+    };
+    let mut parts = range.splitn(3, ':');
+    let first = parts.next().unwrap_or_default();
+    let start = match first.parse::<usize>() {
+        Ok(line) => Some(line.saturating_sub(1)),
+        Err(_) if first.is_empty() => None,
+        Err(_) => return (path, Selection::Anchor(first)),
+    };
+    let selection = match (start, parts.next().map(str::parse::<usize>)) {
+        (Some(start), Some(Ok(end))) => Selection::Lines(Some(start), Some(end)),
+        (Some(start), Some(Err(_))) => Selection::Lines(Some(start), None),
+        (Some(start), None) => Selection::Lines(Some(start), Some(start + 1)),
+        (None, Some(Ok(end))) => Selection::Lines(None, Some(end)),
+        (None, _) => Selection::Lines(None, None),
+    };
+    (path, selection)
+}
+
+/// The anchor name following `marker` (`ANCHOR:` or `ANCHOR_END:`) in
+/// `line`, wherever it appears (usually inside a comment).
+fn anchor_name<'l>(line: &'l str, marker: &str) -> Option<&'l str> {
+    let rest = line[line.find(marker)? + marker.len()..].trim_start();
+    let end = rest
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(rest.len());
+    (end > 0).then(|| &rest[..end])
+}
+
+fn is_anchor_line(line: &str) -> bool {
+    anchor_name(line, "ANCHOR:").is_some() || anchor_name(line, "ANCHOR_END:").is_some()
+}
+
+/// Take the selected lines of `text`. With `hide_rest` (`rustdoc_include`)
+/// every other line is kept too, but hidden behind rustdoc's `# ` prefix.
+/// Anchor marker lines inside an anchored section are always dropped.
+fn select_lines(text: &str, selection: &Selection<'_>, hide_rest: bool) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    match *selection {
+        Selection::Lines(start, end) => {
+            let range = start.unwrap_or(0)..end.unwrap_or(usize::MAX);
+            for (idx, line) in text.lines().enumerate() {
+                if range.contains(&idx) {
+                    lines.push(line.to_string());
+                } else if hide_rest {
+                    lines.push(format!("# {line}"));
+                }
+            }
+        }
+        Selection::Anchor(anchor) => {
+            let mut inside = false;
+            for line in text.lines() {
+                if inside {
+                    if anchor_name(line, "ANCHOR_END:") == Some(anchor) {
+                        inside = false;
+                        if !hide_rest {
+                            break;
+                        }
+                    } else if !is_anchor_line(line) {
+                        lines.push(line.to_string());
+                    }
+                } else if let Some(name) = anchor_name(line, "ANCHOR:") {
+                    inside = name == anchor;
+                } else if hide_rest && anchor_name(line, "ANCHOR_END:").is_none() {
+                    lines.push(format!("# {line}"));
+                }
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+/// A `{{#kind args}}` directive at the start of some text.
+struct Directive<'a> {
+    kind: &'a str,
+    args: &'a str,
+    /// Length of the whole `{{...}}`.
+    len: usize,
+}
+
+/// Parse a directive at the start of `text`, which begins with `{{`.
+/// Whitespace may follow the braces, the kind is `[A-Za-z0-9_]+` and the
+/// arguments run to the first `}}`.
+fn parse_directive(text: &str) -> Option<Directive<'_>> {
+    let after_hash = text.strip_prefix("{{")?.trim_start().strip_prefix('#')?;
+    let kind_len = after_hash
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(after_hash.len());
+    let (kind, rest) = after_hash.split_at(kind_len);
+    if kind.is_empty() || !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let end = rest.find('}')?;
+    let args = rest[..end].trim();
+    if args.is_empty() || !rest[end..].starts_with("}}") {
+        return None;
+    }
+    Some(Directive {
+        kind,
+        args,
+        len: text.len() - rest.len() + end + 2,
+    })
+}
+
+/// What a directive expands to, or `None` to leave it as written (a kind
+/// mdBook doesn't know either).
+fn render(
+    directive: &Directive<'_>,
+    dir: Option<&Path>,
+    depth: usize,
+    unresolved: &mut Vec<PathBuf>,
+) -> Option<String> {
+    let kind = directive.kind;
+    match kind {
+        // Sets the page's <title> in the book; nothing to show in a feed.
+        "title" => return Some(String::new()),
+        "include" | "rustdoc_include" | "playground" | "playpen" => {}
+        _ => return None,
+    }
+    let mut args = directive.args.split_whitespace();
+    let spec = args.next().unwrap_or_default();
+    let (path, selection) = match kind {
+        "include" | "rustdoc_include" => parse_include(spec),
+        _ => (spec, Selection::Lines(None, None)),
+    };
+    let Some(dir) = dir.filter(|_| depth < MAX_INCLUDE_DEPTH) else {
+        return Some(String::new());
+    };
+    let file = dir.join(path);
+    let Ok(text) = fs::read_to_string(&file) else {
+        unresolved.push(file);
+        return Some(String::new());
+    };
+    let body = match kind {
+        "include" => select_lines(&text, &selection, false),
+        "rustdoc_include" => select_lines(&text, &selection, true),
+        _ => {
+            let attrs: Vec<&str> = args.collect();
+            let info = if attrs.is_empty() {
+                "rust".to_string()
+            } else {
+                format!("rust,{}", attrs.join(","))
+            };
+            let newline = if text.ends_with('\n') { "" } else { "\n" };
+            format!("```{info}\n{text}{newline}```\n")
+        }
+    };
+    Some(expand(&body, file.parent(), depth + 1, unresolved))
+}
+
+fn expand(
+    markdown: &str,
+    dir: Option<&Path>,
+    depth: usize,
+    unresolved: &mut Vec<PathBuf>,
+) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut rest = markdown;
+    while let Some(open) = rest.find("{{") {
+        // `\{{#...}}` is escaped: drop the backslash and keep everything up
+        // to the line's last `}}` as written.
+        if rest[..open].ends_with('\\') && rest[open + 2..].starts_with('#') {
+            let line_end = rest[open..].find('\n').map_or(rest.len(), |i| open + i);
+            if let Some(close) = rest[open..line_end].rfind("}}") {
+                let end = open + close + 2;
+                out.push_str(&rest[..open - 1]);
+                out.push_str(&rest[open..end]);
+                rest = &rest[end..];
+                continue;
+            }
+        }
+        out.push_str(&rest[..open]);
+        let text = &rest[open..];
+        if let Some(directive) = parse_directive(text) {
+            match render(&directive, dir, depth, unresolved) {
+                Some(expanded) => out.push_str(&expanded),
+                None => out.push_str(&text[..directive.len]),
+            }
+            rest = &text[directive.len..];
+        } else {
+            out.push('{');
+            rest = &text[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Expand mdBook's `{{#include}}`, `{{#rustdoc_include}}` and
+/// `{{#playground}}` directives the way mdBook does, with paths relative to
+/// `chapter_dir`, and remove `{{#title}}`. Anchors (`file.rs:name`) and line
+/// ranges (`file.rs:3:7`) are supported, included files are expanded in
+/// turn, and `\{{#...}}` is unescaped.
+///
+/// Includes are removed instead when there is no `chapter_dir`, or when the
+/// file can't be read; the paths of the latter are returned alongside.
+#[must_use]
+pub fn expand_directives(markdown: &str, chapter_dir: Option<&Path>) -> (String, Vec<PathBuf>) {
+    let mut unresolved = Vec::new();
+    let expanded = expand(markdown, chapter_dir, 0, &mut unresolved);
+    (expanded, unresolved)
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LISTING: &str = "\
+use std::io;
+// ANCHOR: main
+fn main() {
+    // ANCHOR: body
+    println!(\"hi\");
+    // ANCHOR_END: body
+}
+// ANCHOR_END: main
+";
+
+    fn book() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("listings")).unwrap();
+        fs::write(dir.path().join("listings/main.rs"), LISTING).unwrap();
+        fs::write(
+            dir.path().join("listings/nested.md"),
+            "Nested: {{#include main.rs:2}}",
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn parse_include_understands_ranges_and_anchors() {
+        use Selection::{Anchor, Lines};
+        assert_eq!(parse_include("a.rs"), ("a.rs", Lines(None, None)));
+        assert_eq!(parse_include("a.rs:main"), ("a.rs", Anchor("main")));
+        assert_eq!(parse_include("a.rs:3"), ("a.rs", Lines(Some(2), Some(3))));
+        assert_eq!(parse_include("a.rs:3:"), ("a.rs", Lines(Some(2), None)));
+        assert_eq!(parse_include("a.rs::7"), ("a.rs", Lines(None, Some(7))));
+        assert_eq!(parse_include("a.rs:3:7"), ("a.rs", Lines(Some(2), Some(7))));
+    }
+
+    #[test]
+    fn include_takes_anchors_and_line_ranges() {
+        let dir = book();
+        let md = "```rust\n{{#include listings/main.rs:main}}\n```\n\
+                  {{ #include listings/main.rs:1 }} and {{#include listings/nested.md}}";
+        let (out, unresolved) = expand_directives(md, Some(dir.path()));
+        assert_eq!(
+            out,
+            "```rust\nfn main() {\n    println!(\"hi\");\n}\n```\n\
+             use std::io; and Nested: // ANCHOR: main"
+        );
+        assert!(unresolved.is_empty());
+    }
+
+    #[test]
+    fn rustdoc_include_hides_the_rest() {
+        let dir = book();
+        let (out, _) = expand_directives(
+            "{{#rustdoc_include listings/main.rs:body}}",
+            Some(dir.path()),
+        );
+        assert_eq!(
+            out,
+            "# use std::io;\n# fn main() {\n    println!(\"hi\");\n# }"
+        );
+    }
+
+    #[test]
+    fn playground_becomes_a_rust_block() {
+        let dir = book();
+        let (out, _) = expand_directives(
+            "{{#playground listings/main.rs editable}}",
+            Some(dir.path()),
+        );
+        assert!(out.starts_with("```rust,editable\nuse std::io;\n"), "{out}");
+        assert!(out.ends_with("// ANCHOR_END: main\n```\n"), "{out}");
+    }
+
+    #[test]
+    fn other_directives_are_removed_or_kept() {
+        let dir = book();
+        // An escape runs to the last `}}` on its line.
+        let md = "{{#title Custom}}A \\{{#include x.rs}} {{#include y.rs}}\n\
+                  B {{#unknown y}} {{#include missing.rs}}{{ not a directive }}";
+        let (out, unresolved) = expand_directives(md, Some(dir.path()));
+        assert_eq!(
+            out,
+            "A {{#include x.rs}} {{#include y.rs}}\nB {{#unknown y}} {{ not a directive }}"
+        );
+        assert_eq!(unresolved, vec![dir.path().join("missing.rs")]);
+
+        let (out, unresolved) = expand_directives("a{{#include listings/main.rs}}b", None);
+        assert_eq!(out, "ab");
+        assert!(unresolved.is_empty());
+    }
+}
@@ -21,6 +21,25 @@ pub enum FeedError {
         #[source]
         source: walkdir::Error,
     },
+
+    /// A chapter's YAML frontmatter could not be parsed.
+    #[error("invalid frontmatter in {path}:{line}:{column}: {message}")]
+    Frontmatter {
+        path: PathBuf,
+        /// 1-based line in the chapter file (the opening `---` is line 1).
+        line: usize,
+        /// 1-based column within `line`.
+        column: usize,
+        message: String,
+    },
+
+    /// Reading history with `git` failed (not a repository, `git` missing).
+    #[error("failed to read git history for {path}: {message}")]
+    Git { path: PathBuf, message: String },
+
+    /// A [`crate::FeedFormat`] failed to serialize a page.
+    #[error("failed to render {format} feed: {message}")]
+    Render { format: String, message: String },
 }
 
 pub type Result<T> = std::result::Result<T, FeedError>;
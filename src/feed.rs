@@ -1,25 +1,38 @@
-//! Building RSS 2.0 feed pages from collected articles.
+//! Building feed pages from collected articles.
+//!
+//! Articles become a format-neutral [`Feed`] per page; the RSS 2.0 channel
+//! is rendered from it here, Atom and JSON Feed in their own modules.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 use std::str::FromStr;
 
-use rss::extension::{Extension, ExtensionBuilder};
-use rss::{Channel, ChannelBuilder, Guid, Item, ItemBuilder};
+use chrono::{DateTime, SecondsFormat, SubsecRound, Utc};
+use rss::extension::dublincore::DublinCoreExtension;
+use rss::extension::{Extension, ExtensionBuilder, ExtensionMap};
+use rss::{Category, Channel, ChannelBuilder, Guid, Item, ItemBuilder};
 
 use crate::article::{Article, collect_articles};
+use crate::date_source::DateSource;
+use crate::diagnostics::{DiagnosticKind, Diagnostics};
 use crate::error::Result;
-use crate::frontmatter::FeedVisibility;
-use crate::preview::render_preview;
-
-/// One generated RSS feed file.
+use crate::format::{FeedFormat, RssFormat};
+use crate::frontmatter::{FeedVisibility, FrontMatter};
+use crate::html::Allowlist;
+use crate::model::{Feed, FeedEntry, Person};
+use crate::preview::{PreviewOptions, render_preview};
+use crate::url::md_to_html_path;
+
+/// One page of a generated feed.
 ///
-/// `filename` is the relative file name written into `src/` (for example
-/// `rss.xml` or `rss2.xml`). `channel` is the corresponding RSS 2.0 channel.
+/// `filename` is the RSS file name relative to the output directory (for
+/// example `rss.xml` or `rss2.xml`). `feed` holds the page's entries for
+/// rendering other formats; `channel` is the RSS 2.0 rendering of it.
 pub struct FeedPage {
     /// e.g. "rss.xml", "rss2.xml"
     pub filename: String,
     pub channel: Channel,
+    pub feed: Feed,
 }
 
 /// Result of building feeds for a book.
@@ -29,6 +42,15 @@ pub struct FeedPage {
 /// `rss2.xml`, `rss3.xml`, …) each with a slice of the overall item list.
 pub struct BuildResult {
     pub pages: Vec<FeedPage>,
+    /// Directory the pages live in, relative to the output root: `""` for
+    /// the main feed, `"tags/rust"` or `"categories/guides"` for topic feeds.
+    pub dir: String,
+    /// File name stem replacing `rss`, `atom` and `feed`, see
+    /// [`FeedOptions::output_stem`].
+    pub stem: Option<String>,
+    /// One extra result per tag and category when
+    /// [`FeedOptions::tag_feeds`] is set. Always empty on the nested results.
+    pub tag_feeds: Vec<BuildResult>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -57,17 +79,81 @@ impl FromStr for DefaultBehavior {
 /// Grouping these avoids a long positional-argument list at the call site
 /// (see `build_feed`) and makes it cheap to add new options later without
 /// breaking every caller.
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone)]
 pub struct FeedOptions<'a> {
     pub title: &'a str,
     pub site_url: &'a str,
     pub description: &'a str,
     pub full_preview: bool,
+    /// Put the whole chapter in `content:encoded` (Atom `<content>`, JSON
+    /// Feed `content_html`) and keep the excerpt in `<description>`. Takes
+    /// precedence over `full_preview`.
+    pub full_content: bool,
+    /// How previews are shortened: excerpt separator, length limits and the
+    /// "Read more" link.
+    pub preview: PreviewOptions<'a>,
+    /// Strip chapter HTML down to this allowlist before it goes into the
+    /// feed; `None` keeps it as written.
+    pub sanitize: Option<&'a Allowlist>,
     pub max_items: usize,
     pub paginated: bool,
     pub default_behavior: DefaultBehavior,
     pub strict: bool,
+    /// Email for authors that don't have their own, so RSS can still emit
+    /// `<author>`.
     pub author_email: Option<String>,
+    /// `[preprocessor.rss-feed.authors.<id>]` entries by id. A frontmatter
+    /// `author` given as a plain name matching an id is replaced by the entry.
+    pub author_registry: BTreeMap<String, Person>,
+    /// Also build one feed per tag (`tags/<tag>/rss.xml`) and per category
+    /// (`categories/<category>/rss.xml`).
+    pub tag_feeds: bool,
+    /// Directory the feed is written to, relative to the output root. Empty
+    /// for the main feed.
+    pub output_dir: &'a str,
+    /// File name stem of the pages instead of `rss`, `atom` and `feed`, e.g.
+    /// `"releases"` for `releases.xml`, `releases.atom` and `releases.json`.
+    /// Set for section feeds; topic feeds of the same build use it too.
+    pub output_stem: Option<&'a str>,
+}
+
+impl<'a> FeedOptions<'a> {
+    /// Options for the site at `site_url` with everything else at its
+    /// default: no title or description, no pagination or topic feeds, and
+    /// the main feed's location. Override fields with struct update syntax,
+    /// `FeedOptions { title, ..FeedOptions::new(url) }`.
+    #[must_use]
+    pub fn new(site_url: &'a str) -> Self {
+        Self {
+            title: "",
+            site_url,
+            description: "",
+            full_preview: false,
+            full_content: false,
+            preview: PreviewOptions::default(),
+            sanitize: None,
+            max_items: 0,
+            paginated: false,
+            default_behavior: DefaultBehavior::IncludeAll,
+            strict: false,
+            author_email: None,
+            author_registry: BTreeMap::new(),
+            tag_feeds: false,
+            output_dir: "",
+            output_stem: None,
+        }
+    }
+}
+
+/// Title, description and output location shared by every page of one feed.
+struct ChannelMeta<'a> {
+    title: &'a str,
+    description: &'a str,
+    /// Relative output directory, `""` for the book root.
+    dir: &'a str,
+    /// See [`FeedOptions::output_stem`].
+    stem: Option<&'a str>,
 }
 
 /// Return `true` if this article should appear in the feed given `default_behavior`.
@@ -84,11 +170,19 @@ fn article_is_included(article: &Article, default_behavior: &DefaultBehavior) ->
 /// Build the absolute `.html` link for an article, given its `src`-relative
 /// markdown path.
 fn article_link(base_url: &str, article_path: &str) -> String {
-    let html_path = article_path
-        .replace('\\', "/")
-        .replace(".md", ".html")
-        .replace("/README.html", "/index.html");
-    format!("{base_url}/{html_path}")
+    format!("{base_url}/{}", md_to_html_path(article_path))
+}
+
+/// Collect an article's `categories` then `tags`, dropping exact duplicates
+/// while keeping first-seen order.
+fn entry_categories(fm: &FrontMatter) -> Vec<String> {
+    let mut seen: Vec<String> = Vec::new();
+    for name in fm.categories.iter().chain(&fm.tags) {
+        if !seen.contains(name) {
+            seen.push(name.clone());
+        }
+    }
+    seen
 }
 
 /// Build an `atom:link` extension element.
@@ -105,46 +199,144 @@ fn atom_link(href: &str, rel: &str) -> Extension {
         .build()
 }
 
-/// Compute the RSS filename for a given zero-based page index.
-fn rss_filename(page_idx: usize) -> String {
-    if page_idx == 0 {
-        "rss.xml".to_string()
+/// Dublin Core terms namespace, for `dcterms:modified`.
+const DCTERMS_NAMESPACE: &str = "http://purl.org/dc/terms/";
+
+/// Item-level `atom:updated` and `dcterms:modified` elements carrying the
+/// last revision date, which RSS 2.0 has no element for.
+fn updated_extensions(updated: DateTime<Utc>) -> ExtensionMap {
+    let value = updated.to_rfc3339_opts(SecondsFormat::Secs, true);
+    let element = |prefix: &str, name: &str| {
+        let ext = ExtensionBuilder::default()
+            .name(format!("{prefix}:{name}"))
+            .value(Some(value.clone()))
+            .build();
+        (
+            prefix.to_string(),
+            BTreeMap::from([(name.to_string(), vec![ext])]),
+        )
+    };
+    BTreeMap::from([element("atom", "updated"), element("dcterms", "modified")])
+}
+
+/// Prefix `filename` with `dir` unless `dir` is the output root.
+pub(crate) fn page_path(dir: &str, filename: &str) -> String {
+    if dir.is_empty() {
+        filename.to_string()
     } else {
-        format!("rss{}.xml", page_idx + 1)
+        format!("{dir}/{filename}")
+    }
+}
+
+/// Turn a tag or category name into a URL-safe directory name.
+///
+/// Lowercases ASCII letters, keeps alphanumerics (including non-ASCII ones),
+/// and collapses every other run of characters into a single `-`.
+#[must_use]
+pub fn topic_slug(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.trim().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let trimmed = slug.trim_end_matches('-').len();
+    slug.truncate(trimmed);
+    slug
+}
+
+/// Render one RSS item.
+fn rss_item(entry: &FeedEntry) -> Item {
+    let mut item = ItemBuilder::default();
+    item.title(Some(entry.title.clone()));
+    item.link(entry.url.clone());
+    // With a separate summary, the excerpt stays in <description> and the
+    // full chapter goes into content:encoded.
+    match &entry.summary_html {
+        Some(summary) => {
+            item.description(Some(summary.clone()));
+            item.content(Some(entry.content_html.clone()));
+        }
+        None => {
+            item.description(Some(entry.content_html.clone()));
+        }
+    }
+    item.guid(Some(Guid {
+        value: entry.id.clone(),
+        permalink: entry.url.as_deref() == Some(entry.id.as_str()),
+    }));
+    item.categories(
+        entry
+            .categories
+            .iter()
+            .map(|name| Category {
+                name: name.clone(),
+                domain: None,
+            })
+            .collect::<Vec<_>>(),
+    );
+    if let Some(date) = entry.published {
+        // chrono's to_rfc2822() doesn't zero-pad single-digit days,
+        // violating RFC 2822. Format manually to ensure compliance.
+        item.pub_date(Some(date.format("%a, %d %b %Y %T %z").to_string()));
     }
+    if let Some(updated) = entry.updated {
+        item.extensions(updated_extensions(updated));
+    }
+    // RSS allows a single <author>, which must include an email; every
+    // author's name also goes into a dc:creator.
+    if let Some(person) = entry.authors.iter().find(|p| p.email.is_some())
+        && let Some(email) = &person.email
+    {
+        item.author(Some(format!("{email} ({})", person.name)));
+    }
+    if !entry.authors.is_empty() {
+        let mut dc = DublinCoreExtension::default();
+        dc.set_creators(
+            entry
+                .authors
+                .iter()
+                .map(|p| p.name.clone())
+                .collect::<Vec<_>>(),
+        );
+        item.dublin_core_ext(Some(dc));
+    }
+    item.build()
 }
 
-/// Build a single [`Channel`] from a slice of items.
+/// Render a [`Feed`] page as an RSS 2.0 [`Channel`].
 ///
 /// - `rel="self"` — the canonical URL of this page
 /// - `rel="prev"` — the newer page, when this is not the first page
 /// - `rel="next"` — the older page, when this is not the last page
-fn build_channel(
-    title: &str,
-    base_url: &str,
-    description: &str,
-    items: &[Item],
-    page_idx: usize,
-    total_pages: usize,
+#[must_use]
+pub fn feed_to_rss(
+    feed: &Feed,
+    self_url: &str,
+    next_url: Option<&str>,
+    prev_url: Option<&str>,
 ) -> Channel {
     // Atom namespace links for pagination discovery.
-    let self_url = format!("{base_url}/{}", rss_filename(page_idx));
-    let mut atom_links = vec![atom_link(&self_url, "self")];
-
-    if page_idx > 0 {
-        let prev_url = format!("{base_url}/{}", rss_filename(page_idx - 1));
-        atom_links.push(atom_link(&prev_url, "prev"));
+    let mut atom_links = vec![atom_link(self_url, "self")];
+    if let Some(prev) = prev_url {
+        atom_links.push(atom_link(prev, "prev"));
     }
-    if page_idx + 1 < total_pages {
-        let next_url = format!("{base_url}/{}", rss_filename(page_idx + 1));
-        atom_links.push(atom_link(&next_url, "next"));
+    if let Some(next) = next_url {
+        atom_links.push(atom_link(next, "next"));
     }
 
+    let items: Vec<Item> = feed.entries.iter().map(rss_item).collect();
+
     let mut namespaces = BTreeMap::new();
     namespaces.insert(
         "atom".to_string(),
         "http://www.w3.org/2005/Atom".to_string(),
     );
+    if items.iter().any(|i| i.extensions().contains_key("dcterms")) {
+        namespaces.insert("dcterms".to_string(), DCTERMS_NAMESPACE.to_string());
+    }
 
     let mut inner: BTreeMap<String, Vec<Extension>> = BTreeMap::new();
     inner.insert("link".to_string(), atom_links);
@@ -152,10 +344,10 @@ fn build_channel(
     extensions.insert("atom".to_string(), inner);
 
     ChannelBuilder::default()
-        .title(title)
-        .link(format!("{base_url}/"))
-        .description(description)
-        .items(items.to_vec())
+        .title(feed.title.clone())
+        .link(feed.home_url.clone())
+        .description(feed.description.clone())
+        .items(items)
         .generator(Some(format!(
             "mdbook-rss-feed {}",
             env!("CARGO_PKG_VERSION")
@@ -165,98 +357,303 @@ fn build_channel(
         .build()
 }
 
-/// Split `items` into one or more [`FeedPage`]s according to `opts`.
-fn paginate(items: &[Item], opts: &FeedOptions<'_>, base_url: &str) -> Vec<FeedPage> {
-    let mut pages: Vec<FeedPage> = Vec::new();
+/// Build page `page_idx` of `total_pages` from its slice of entries.
+fn build_page(
+    meta: &ChannelMeta<'_>,
+    base_url: &str,
+    entries: &[FeedEntry],
+    page_idx: usize,
+    total_pages: usize,
+) -> FeedPage {
+    let feed = Feed {
+        title: meta.title.to_string(),
+        description: meta.description.to_string(),
+        home_url: format!("{base_url}/"),
+        entries: entries.to_vec(),
+    };
+    let links = RssFormat.page_links(base_url, meta.dir, meta.stem, page_idx, total_pages);
+    FeedPage {
+        filename: page_path(meta.dir, &RssFormat.file_name(meta.stem, page_idx)),
+        channel: feed_to_rss(
+            &feed,
+            &links.self_url,
+            links.next_url.as_deref(),
+            links.prev_url.as_deref(),
+        ),
+        feed,
+    }
+}
 
-    let should_paginate = opts.paginated && opts.max_items > 0 && items.len() > opts.max_items;
+/// Split `entries` into one or more [`FeedPage`]s according to `opts`.
+fn paginate(
+    entries: &[FeedEntry],
+    meta: &ChannelMeta<'_>,
+    opts: &FeedOptions<'_>,
+    base_url: &str,
+) -> Vec<FeedPage> {
+    let should_paginate = opts.paginated && opts.max_items > 0 && entries.len() > opts.max_items;
     if !should_paginate {
-        let channel = build_channel(opts.title, base_url, opts.description, items, 0, 1);
-        return vec![FeedPage {
-            filename: "rss.xml".to_string(),
-            channel,
-        }];
-    }
-
-    let total_pages = items.len().div_ceil(opts.max_items);
-    for page_idx in 0..total_pages {
-        let start = page_idx * opts.max_items;
-        let end = (start + opts.max_items).min(items.len());
-        let channel = build_channel(
-            opts.title,
-            base_url,
-            opts.description,
-            &items[start..end],
-            page_idx,
-            total_pages,
+        return vec![build_page(meta, base_url, entries, 0, 1)];
+    }
+
+    let total_pages = entries.len().div_ceil(opts.max_items);
+    entries
+        .chunks(opts.max_items)
+        .enumerate()
+        .map(|(page_idx, chunk)| build_page(meta, base_url, chunk, page_idx, total_pages))
+        .collect()
+}
+
+/// Group article indices by tag and by category.
+///
+/// Keys are `(kind, slug)` where `kind` is `"tags"` or `"categories"`; the
+/// value holds the first-seen display name and the indices of matching
+/// articles in their original (newest-first) order.
+fn group_by_topic(articles: &[Article]) -> BTreeMap<(&'static str, String), (String, Vec<usize>)> {
+    let mut groups: BTreeMap<(&'static str, String), (String, Vec<usize>)> = BTreeMap::new();
+    for (idx, article) in articles.iter().enumerate() {
+        let labelled = article
+            .fm
+            .tags
+            .iter()
+            .map(|t| ("tags", t))
+            .chain(article.fm.categories.iter().map(|c| ("categories", c)));
+        for (kind, name) in labelled {
+            let slug = topic_slug(name);
+            if slug.is_empty() {
+                continue;
+            }
+            let entry = groups
+                .entry((kind, slug))
+                .or_insert_with(|| (name.clone(), Vec::new()));
+            if entry.1.last() != Some(&idx) {
+                entry.1.push(idx);
+            }
+        }
+    }
+    groups
+}
+
+/// Build one [`BuildResult`] per tag and category found in `articles`.
+///
+/// `entries` must be the entries for `articles`, index for index.
+fn build_topic_feeds(
+    articles: &[Article],
+    entries: &[FeedEntry],
+    opts: &FeedOptions<'_>,
+    base_url: &str,
+) -> Vec<BuildResult> {
+    group_by_topic(articles)
+        .into_iter()
+        .map(|((kind, slug), (name, indices))| {
+            let dir = page_path(opts.output_dir, &format!("{kind}/{slug}"));
+            let title = format!("{} - {name}", opts.title);
+            let description = if kind == "tags" {
+                format!("Chapters tagged \"{name}\"")
+            } else {
+                format!("Chapters in category \"{name}\"")
+            };
+            let topic_entries: Vec<FeedEntry> =
+                indices.into_iter().map(|i| entries[i].clone()).collect();
+            let meta = ChannelMeta {
+                title: &title,
+                description: &description,
+                dir: &dir,
+                stem: opts.output_stem,
+            };
+            BuildResult {
+                pages: paginate(&topic_entries, &meta, opts, base_url),
+                dir,
+                stem: opts.output_stem.map(str::to_string),
+                tag_feeds: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// Record the per-chapter problems that only show up once an article is
+/// turned into a feed entry. An author without an email is only reported
+/// for the first chapter they appear on.
+fn check_item(
+    article: &Article,
+    entry: &FeedEntry,
+    seen_links: &mut HashMap<String, String>,
+    reported_authors: &mut HashSet<String>,
+    diagnostics: &mut Diagnostics,
+) {
+    let path = article.path.as_str();
+    if article.fm.date.is_none() {
+        diagnostics.warn(
+            path,
+            DiagnosticKind::MissingDate,
+            "no `date` in frontmatter; the item has no publish date and sorts last",
         );
-        pages.push(FeedPage {
-            filename: rss_filename(page_idx),
-            channel,
-        });
     }
-    pages
+    for person in entry.authors.iter().filter(|p| p.email.is_none()) {
+        if !reported_authors.insert(person.name.clone()) {
+            continue;
+        }
+        diagnostics.warn(
+            path,
+            DiagnosticKind::MissingAuthorEmail,
+            format!(
+                "author `{}` has no email, so RSS only lists them as <dc:creator>; \
+                 set `email` or `author-email`",
+                person.name
+            ),
+        );
+    }
+    if entry.content_html.trim().is_empty() {
+        diagnostics.warn(
+            path,
+            DiagnosticKind::EmptyPreview,
+            "the preview is empty; add body text or a `description`",
+        );
+    }
+    let link = entry.id.as_str();
+    match seen_links.get(link) {
+        Some(first) if first != path => diagnostics.warn(
+            path,
+            DiagnosticKind::DuplicateLink,
+            format!("links to {link}, same as {first}"),
+        ),
+        Some(_) => {}
+        None => {
+            seen_links.insert(link.to_string(), path.to_string());
+        }
+    }
 }
 
-/// Convert a list of [`Article`]s into RSS [`Item`]s, applying filtering.
-fn articles_to_items(articles: Vec<Article>, opts: &FeedOptions<'_>, base_url: &str) -> Vec<Item> {
+/// Resolve frontmatter authors: a plain name matching a registry id becomes
+/// that entry, and `author-email` fills in a missing email.
+fn resolve_authors(authors: &[Person], opts: &FeedOptions<'_>) -> Vec<Person> {
+    authors
+        .iter()
+        .map(|person| {
+            let mut person = match opts.author_registry.get(&person.name) {
+                Some(entry) if person.is_name_only() => entry.clone(),
+                _ => person.clone(),
+            };
+            if person.email.is_none() {
+                person.email.clone_from(&opts.author_email);
+            }
+            person
+        })
+        .collect()
+}
+
+/// Convert a list of already-filtered [`Article`]s into [`FeedEntry`]s.
+fn articles_to_entries(
+    articles: &[Article],
+    opts: &FeedOptions<'_>,
+    base_url: &str,
+    diagnostics: &mut Diagnostics,
+) -> Vec<FeedEntry> {
+    let mut seen_links = HashMap::new();
+    let mut reported_authors = HashSet::new();
     articles
-        .into_iter()
-        .filter(|a| article_is_included(a, &opts.default_behavior))
+        .iter()
         .map(|article| {
             let link = article_link(base_url, &article.path);
-            let preview = render_preview(
-                &article.content,
-                article.fm.description.as_deref(),
-                opts.full_preview,
-                base_url,
-                Some(&link),
+            let preview_opts = opts.preview.with_overrides(&article.fm.preview);
+            let preview = |full: bool| {
+                render_preview(
+                    &article.content,
+                    article.fm.description.as_deref(),
+                    full,
+                    &preview_opts,
+                    opts.sanitize,
+                    base_url,
+                    Some(&link),
+                )
+            };
+            let (content_html, summary_html) = if opts.full_content {
+                (preview(true), Some(preview(false)))
+            } else {
+                (preview(opts.full_preview), None)
+            };
+            let entry = FeedEntry {
+                id: link.clone(),
+                title: article.fm.title.clone(),
+                url: Some(link),
+                content_html,
+                summary_html,
+                // Whole seconds, as RSS dates have; mtimes carry nanoseconds.
+                published: article.fm.date.map(|d| d.trunc_subsecs(0)),
+                updated: article.fm.updated.map(|d| d.trunc_subsecs(0)),
+                authors: resolve_authors(&article.fm.authors, opts),
+                categories: entry_categories(&article.fm),
+            };
+            check_item(
+                article,
+                &entry,
+                &mut seen_links,
+                &mut reported_authors,
+                diagnostics,
             );
-
-            let mut item = ItemBuilder::default();
-            item.title(Some(article.fm.title.clone()));
-            item.link(Some(link.clone()));
-            item.description(Some(preview));
-            item.guid(Some(Guid {
-                value: link,
-                permalink: true,
-            }));
-            if let Some(date) = article.fm.date {
-                // chrono's to_rfc2822() doesn't zero-pad single-digit days,
-                // violating RFC 2822. Format manually to ensure compliance.
-                item.pub_date(Some(date.format("%a, %d %b %Y %T %z").to_string()));
-            }
-            if let Some(author) = article.fm.author
-                && let Some(email) = &opts.author_email
-            {
-                item.author(Some(format!("{email} ({author})")));
-            }
-            item.build()
+            entry
         })
         .collect()
 }
 
+/// Build the feed pages for a list of articles.
+///
+/// Articles are filtered by `default-behavior`/`feed:` first. When
+/// [`FeedOptions::tag_feeds`] is set, [`BuildResult::tag_feeds`] also holds
+/// one paginated feed per tag and category.
+///
+/// Problems with individual chapters (missing date, empty preview, ...) are
+/// recorded in `diagnostics`.
 #[must_use]
-pub fn build_feed_from_articles(articles: Vec<Article>, opts: &FeedOptions<'_>) -> BuildResult {
+pub fn build_feed_from_articles(
+    articles: Vec<Article>,
+    opts: &FeedOptions<'_>,
+    diagnostics: &mut Diagnostics,
+) -> BuildResult {
     let base_url = opts.site_url.trim_end_matches('/');
-    let items = articles_to_items(articles, opts, base_url);
+    let articles: Vec<Article> = articles
+        .into_iter()
+        .filter(|a| article_is_included(a, &opts.default_behavior))
+        .collect();
+    let entries = articles_to_entries(&articles, opts, base_url, diagnostics);
+
+    let tag_feeds = if opts.tag_feeds {
+        build_topic_feeds(&articles, &entries, opts, base_url)
+    } else {
+        Vec::new()
+    };
+
+    let meta = ChannelMeta {
+        title: opts.title,
+        description: opts.description,
+        dir: opts.output_dir,
+        stem: opts.output_stem,
+    };
     BuildResult {
-        pages: paginate(&items, opts, base_url),
+        pages: paginate(&entries, &meta, opts, base_url),
+        dir: opts.output_dir.to_string(),
+        stem: opts.output_stem.map(str::to_string),
+        tag_feeds,
     }
 }
 
 /// Build one or more RSS 2.0 feeds by scanning `src_dir` on disk.
 ///
-/// **Legacy path.** Does not expand `{{#include}}` directives and includes
-/// all `.md` files, not just those listed in `SUMMARY.md`. Prefer
+/// **Legacy path.** Includes all `.md` files, not just those listed in
+/// `SUMMARY.md`. Prefer
 /// [`build_feed_from_articles`] with [`crate::articles_from_book_json`] when
 /// running as an mdBook preprocessor.
 ///
 /// # Errors
 /// Returns `Err` if `src_dir` can't be accessed or walked.
-pub fn build_feed(src_dir: &Path, opts: &FeedOptions<'_>) -> Result<BuildResult> {
-    let articles = collect_articles(src_dir, opts.strict)?;
-    Ok(build_feed_from_articles(articles, opts))
+pub fn build_feed(
+    src_dir: &Path,
+    opts: &FeedOptions<'_>,
+    dates: &DateSource,
+    diagnostics: &mut Diagnostics,
+) -> Result<BuildResult> {
+    let articles = collect_articles(src_dir, opts.strict, dates, diagnostics)?;
+    Ok(build_feed_from_articles(articles, opts, diagnostics))
 }
 
 #[allow(clippy::unwrap_used)]
@@ -265,6 +662,8 @@ mod tests {
     use super::*;
     use crate::article::Article;
     use crate::frontmatter::{FeedVisibility, FrontMatter};
+    use crate::math::MathMode;
+    use crate::preview::{PreviewOverrides, PreviewUnit};
 
     // ── Helpers ───────────────────────────────────────────────────────────────
 
@@ -283,9 +682,13 @@ mod tests {
             fm: FrontMatter {
                 title: title.to_string(),
                 date,
-                author: None,
+                updated: None,
+                authors: Vec::new(),
                 description: None,
                 feed,
+                tags: Vec::new(),
+                categories: Vec::new(),
+                preview: PreviewOverrides::default(),
             },
             content: format!("# {title}\n\nSome content for {title}."),
             path: path.to_string(),
@@ -295,14 +698,9 @@ mod tests {
     fn default_opts(site_url: &str) -> FeedOptions<'_> {
         FeedOptions {
             title: "Test Blog",
-            site_url,
             description: "A test blog.",
-            full_preview: false,
             max_items: 10,
-            paginated: false,
-            default_behavior: DefaultBehavior::IncludeAll,
-            strict: false,
-            author_email: None,
+            ..FeedOptions::new(site_url)
         }
     }
 
@@ -355,14 +753,14 @@ mod tests {
 
     #[test]
     fn rss_filename_page_zero_is_rss_xml() {
-        assert_eq!(rss_filename(0), "rss.xml");
+        assert_eq!(RssFormat.file_name(None, 0), "rss.xml");
     }
 
     #[test]
     fn rss_filename_subsequent_pages_are_numbered() {
-        assert_eq!(rss_filename(1), "rss2.xml");
-        assert_eq!(rss_filename(2), "rss3.xml");
-        assert_eq!(rss_filename(9), "rss10.xml");
+        assert_eq!(RssFormat.file_name(None, 1), "rss2.xml");
+        assert_eq!(RssFormat.file_name(None, 2), "rss3.xml");
+        assert_eq!(RssFormat.file_name(None, 9), "rss10.xml");
     }
 
     // ── article_is_included ───────────────────────────────────────────────────
@@ -406,7 +804,7 @@ mod tests {
             make_article("Post B", "b.md", Some("2024-05-01T00:00:00Z"), None),
         ];
         let opts = default_opts("https://example.com");
-        let result = build_feed_from_articles(articles, &opts);
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
         assert_eq!(result.pages.len(), 1);
         let channel = &result.pages[0].channel;
         assert_eq!(channel.items().len(), 2);
@@ -417,7 +815,7 @@ mod tests {
     fn build_feed_from_articles_channel_metadata() {
         let articles = vec![make_article("Post", "post.md", None, None)];
         let opts = default_opts("https://myblog.com");
-        let result = build_feed_from_articles(articles, &opts);
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
         let channel = &result.pages[0].channel;
         assert_eq!(channel.title(), "Test Blog");
         assert_eq!(channel.description(), "A test blog.");
@@ -428,7 +826,7 @@ mod tests {
     fn build_feed_from_articles_item_link_is_html() {
         let articles = vec![make_article("Post", "posts/my-post.md", None, None)];
         let opts = default_opts("https://example.com");
-        let result = build_feed_from_articles(articles, &opts);
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
         let item = &result.pages[0].channel.items()[0];
         let link = item.link().unwrap();
 
@@ -452,7 +850,7 @@ mod tests {
             None,
         )];
         let opts = default_opts("https://example.com");
-        let result = build_feed_from_articles(articles, &opts);
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
         let item = &result.pages[0].channel.items()[0];
         let pub_date = item.pub_date().unwrap();
         // The day portion should be "05", never "5".
@@ -462,6 +860,245 @@ mod tests {
         );
     }
 
+    #[test]
+    fn build_feed_from_articles_emits_updated_extensions() {
+        let mut article = make_article("Post", "post.md", Some("2024-01-05T00:00:00Z"), None);
+        article.fm.updated = chrono::DateTime::parse_from_rfc3339("2024-02-01T08:30:00+02:00")
+            .ok()
+            .map(|dt| dt.with_timezone(&chrono::Utc));
+        let plain = make_article("Plain", "plain.md", None, None);
+        let opts = default_opts("https://example.com");
+        let result = build_feed_from_articles(vec![article, plain], &opts, &mut Diagnostics::new());
+        let channel = &result.pages[0].channel;
+        let ext = |item: &Item, prefix: &str, name: &str| {
+            item.extensions()
+                .get(prefix)
+                .and_then(|m| m.get(name))
+                .and_then(|v| v.first())
+                .and_then(|e| e.value().map(str::to_string))
+        };
+        let items = channel.items();
+        assert_eq!(
+            ext(&items[0], "atom", "updated").as_deref(),
+            Some("2024-02-01T06:30:00Z")
+        );
+        assert_eq!(
+            ext(&items[0], "dcterms", "modified").as_deref(),
+            Some("2024-02-01T06:30:00Z")
+        );
+        assert!(items[1].extensions().is_empty());
+        assert_eq!(
+            channel.namespaces().get("dcterms").map(String::as_str),
+            Some(DCTERMS_NAMESPACE)
+        );
+        assert!(
+            channel
+                .to_string()
+                .contains("<dcterms:modified>2024-02-01T06:30:00Z</dcterms:modified>")
+        );
+    }
+
+    #[test]
+    fn pages_carry_the_format_neutral_feed() {
+        let mut article = make_article("Post", "post.md", Some("2024-01-05T00:00:00Z"), None);
+        article.fm.authors = vec![Person::named("Jane")];
+        let opts = default_opts("https://example.com");
+        let result = build_feed_from_articles(vec![article], &opts, &mut Diagnostics::new());
+        let page = &result.pages[0];
+        assert_eq!(page.feed.home_url, "https://example.com/");
+        let entry = &page.feed.entries[0];
+        assert_eq!(entry.url.as_deref(), Some("https://example.com/post.html"));
+        // No `author-email`: RSS drops the author, the model keeps the name.
+        assert_eq!(entry.authors[0].name, "Jane");
+        assert_eq!(page.channel.items()[0].author(), None);
+        assert_eq!(
+            feed_to_rss(&page.feed, "https://example.com/rss.xml", None, None).to_string(),
+            page.channel.to_string()
+        );
+    }
+
+    #[test]
+    fn html_block_previews_are_cut_within_the_block() {
+        let mut article = make_article("Table", "table.md", Some("2024-01-05T00:00:00Z"), None);
+        let rows = (1..=200)
+            .map(|i| format!("<tr><td>Row {i}</td><td>with a few words</td></tr>"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        article.content = format!("<table>\n{rows}\n</table>\n");
+        assert!(article.content.len() > 4000);
+        let opts = default_opts("https://example.com");
+        let result = build_feed_from_articles(vec![article], &opts, &mut Diagnostics::new());
+
+        let description = result.pages[0].channel.items()[0].description().unwrap();
+        assert!(
+            description.ends_with("…</td></tr></table>"),
+            "{description}"
+        );
+        assert!(!description.contains("Row 200"), "{description}");
+        // Whitespace between tags doesn't count; the ellipsis does.
+        let text: String = description
+            .split('<')
+            .filter_map(|part| part.split_once('>').map(|(_, text)| text))
+            .filter(|text| !text.trim().is_empty())
+            .collect();
+        assert!(
+            text.chars().count() <= PreviewOptions::default().max_length + 1,
+            "{text}"
+        );
+    }
+
+    #[test]
+    fn full_content_keeps_the_excerpt_in_description() {
+        let mut article = make_article("Post", "post.md", Some("2024-01-05T00:00:00Z"), None);
+        article.content = (1..=5)
+            .map(|i| format!("Paragraph {i} of the chapter body."))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let mut opts = default_opts("https://example.com");
+        opts.full_content = true;
+        let result = build_feed_from_articles(vec![article], &opts, &mut Diagnostics::new());
+
+        let entry = &result.pages[0].feed.entries[0];
+        let summary = entry.summary_html.as_deref().unwrap();
+        assert!(!summary.contains("Paragraph 4"));
+        assert!(entry.content_html.contains("Paragraph 5"));
+
+        let channel = &result.pages[0].channel;
+        let item = &channel.items()[0];
+        assert_eq!(item.description(), Some(summary));
+        assert_eq!(item.content(), Some(entry.content_html.as_str()));
+        assert!(
+            channel
+                .to_string()
+                .contains("xmlns:content=\"http://purl.org/rss/1.0/modules/content/\"")
+        );
+
+        // The split survives a round trip through RSS.
+        let recovered = Feed::from_channel(channel);
+        assert_eq!(recovered.entries[0].content_html, entry.content_html);
+        assert_eq!(recovered.entries[0].summary_html.as_deref(), Some(summary));
+    }
+
+    #[test]
+    fn sanitize_applies_to_excerpt_and_full_content() {
+        let mut article = make_article("Post", "posts/post.md", Some("2024-01-05T00:00:00Z"), None);
+        article.content = "Intro with <img src=\"a.png\" onerror=\"x()\"> an image.\n\n\
+                           <script>alert(1)</script>\n\n<!-- more -->\n\nThe rest."
+            .to_string();
+        let allowlist = Allowlist::default();
+        let mut opts = default_opts("https://example.com");
+        opts.full_content = true;
+        opts.sanitize = Some(&allowlist);
+        let result = build_feed_from_articles(vec![article], &opts, &mut Diagnostics::new());
+
+        let entry = &result.pages[0].feed.entries[0];
+        for html in [&entry.content_html, entry.summary_html.as_ref().unwrap()] {
+            assert!(
+                !html.contains("script") && !html.contains("onerror"),
+                "{html}"
+            );
+            assert!(
+                html.contains(r#"<img src="https://example.com/posts/a.png">"#),
+                "{html}"
+            );
+        }
+    }
+
+    #[test]
+    fn sanitized_math_keeps_its_classes() {
+        let mut article = make_article("Post", "posts/post.md", Some("2024-01-05T00:00:00Z"), None);
+        article.content =
+            "Inline $a < b$ and <code class=\"x\">other</code>.\n\n$$\n\\nosuchcommand\n$$\n"
+                .to_string();
+        let mut opts = default_opts("https://example.com");
+        opts.full_preview = true;
+        let check = |mode: MathMode, allowlist: &Allowlist| {
+            let opts = FeedOptions {
+                preview: PreviewOptions {
+                    math: Some(mode),
+                    ..PreviewOptions::default()
+                },
+                sanitize: Some(allowlist),
+                ..opts.clone()
+            };
+            let result =
+                build_feed_from_articles(vec![article.clone()], &opts, &mut Diagnostics::new());
+            result.pages[0].feed.entries[0].content_html.clone()
+        };
+
+        let html = check(MathMode::Code, &Allowlist::default().with_math_classes());
+        assert!(
+            html.contains(r#"<code class="math math-inline">\(a &lt; b\)</code>"#),
+            "{html}"
+        );
+        assert!(html.contains("<code>other</code>"), "{html}");
+        // TeX that doesn't parse as MathML falls back to the same code.
+        let allowlist = Allowlist::default().with_mathml().with_math_classes();
+        let html = check(MathMode::Mathml, &allowlist);
+        assert!(
+            html.contains("<code class=\"math math-display\">\\[\n\\nosuchcommand\n\\]</code>"),
+            "{html}"
+        );
+    }
+
+    #[test]
+    fn chapters_override_preview_sizing() {
+        let body = "A first paragraph long enough to be used as the preview on its own.\n\n\
+                    Second paragraph.";
+        let mut short = make_article("Short", "short.md", Some("2024-01-02T00:00:00Z"), None);
+        short.content = body.to_string();
+        short.fm.preview = PreviewOverrides {
+            paragraphs: Some(1),
+            max_chars: Some(3),
+            unit: Some(PreviewUnit::Words),
+            ..PreviewOverrides::default()
+        };
+        let mut long = make_article("Long", "long.md", Some("2024-01-01T00:00:00Z"), None);
+        long.content = body.to_string();
+
+        let mut opts = default_opts("https://example.com");
+        opts.preview.max_paragraphs = 2;
+        let result = build_feed_from_articles(vec![short, long], &opts, &mut Diagnostics::new());
+        let entries = &result.pages[0].feed.entries;
+        assert_eq!(entries[0].content_html, "<p>A first paragraph…</p>\n");
+        assert!(entries[1].content_html.contains("Second paragraph."));
+    }
+
+    #[test]
+    fn authors_resolve_through_the_registry() {
+        let mut article = make_article("Post", "post.md", Some("2024-01-05T00:00:00Z"), None);
+        article.fm.authors = vec![Person::named("jane"), Person::named("Bob")];
+        let mut opts = default_opts("https://example.com");
+        opts.author_registry.insert(
+            "jane".to_string(),
+            Person {
+                name: "Jane Doe".to_string(),
+                email: Some("jane@example.com".to_string()),
+                url: Some("https://jane.example.com".to_string()),
+                avatar: None,
+            },
+        );
+        let mut diagnostics = Diagnostics::new();
+        let result = build_feed_from_articles(vec![article], &opts, &mut diagnostics);
+
+        let entry = &result.pages[0].feed.entries[0];
+        assert_eq!(entry.authors[0].name, "Jane Doe");
+        assert_eq!(
+            entry.authors[0].url.as_deref(),
+            Some("https://jane.example.com")
+        );
+        assert_eq!(entry.authors[1], Person::named("Bob"));
+
+        let item = &result.pages[0].channel.items()[0];
+        assert_eq!(item.author(), Some("jane@example.com (Jane Doe)"));
+        assert_eq!(
+            item.dublin_core_ext().unwrap().creators(),
+            ["Jane Doe", "Bob"]
+        );
+        // Only Bob lacks an email.
+        assert_eq!(diagnostics.len(), 1);
+    }
+
     #[test]
     fn build_feed_from_articles_filters_excluded_items() {
         let articles = vec![
@@ -469,7 +1106,7 @@ mod tests {
             make_article("Excluded", "exc.md", None, Some(FeedVisibility::Exclude)),
         ];
         let opts = default_opts("https://example.com");
-        let result = build_feed_from_articles(articles, &opts);
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
         let items = result.pages[0].channel.items();
         assert_eq!(items.len(), 1);
         assert_eq!(items[0].title().unwrap(), "Included");
@@ -484,7 +1121,7 @@ mod tests {
         ];
         let mut opts = default_opts("https://example.com");
         opts.default_behavior = DefaultBehavior::ExcludeAll;
-        let result = build_feed_from_articles(articles, &opts);
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
         let items = result.pages[0].channel.items();
         assert_eq!(items.len(), 1);
         assert_eq!(items[0].title().unwrap(), "B");
@@ -495,7 +1132,7 @@ mod tests {
         let articles = vec![make_article("Post", "post.md", None, None)];
         let mut opts = default_opts("https://example.com/");
         opts.max_items = 0; // no pagination
-        let result = build_feed_from_articles(articles, &opts);
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
         let item = &result.pages[0].channel.items()[0];
         let link = item.link().unwrap();
         // Should not have a double slash.
@@ -505,6 +1142,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn build_feed_from_articles_emits_categories_and_tags() {
+        let mut article = make_article("Post", "post.md", None, None);
+        article.fm.categories = vec!["guides".to_string()];
+        article.fm.tags = vec!["rust".to_string(), "guides".to_string()];
+        let opts = default_opts("https://example.com");
+        let result = build_feed_from_articles(vec![article], &opts, &mut Diagnostics::new());
+        let item = &result.pages[0].channel.items()[0];
+        let names: Vec<&str> = item.categories().iter().map(Category::name).collect();
+        assert_eq!(names, vec!["guides", "rust"]);
+    }
+
     // ── pagination ────────────────────────────────────────────────────────────
 
     #[test]
@@ -515,7 +1164,7 @@ mod tests {
         let mut opts = default_opts("https://example.com");
         opts.max_items = 5;
         opts.paginated = false; // disabled
-        let result = build_feed_from_articles(articles, &opts);
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
         assert_eq!(result.pages.len(), 1);
         assert_eq!(result.pages[0].channel.items().len(), 15);
     }
@@ -528,7 +1177,7 @@ mod tests {
         let mut opts = default_opts("https://example.com");
         opts.max_items = 5;
         opts.paginated = true;
-        let result = build_feed_from_articles(articles, &opts);
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
         // 12 items at 5 per page = 3 pages (5, 5, 2).
         assert_eq!(result.pages.len(), 3);
         assert_eq!(result.pages[0].channel.items().len(), 5);
@@ -544,7 +1193,7 @@ mod tests {
         let mut opts = default_opts("https://example.com");
         opts.max_items = 5;
         opts.paginated = true;
-        let result = build_feed_from_articles(articles, &opts);
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
         assert_eq!(result.pages[0].filename, "rss.xml");
         assert_eq!(result.pages[1].filename, "rss2.xml");
         assert_eq!(result.pages[2].filename, "rss3.xml");
@@ -558,7 +1207,7 @@ mod tests {
         let mut opts = default_opts("https://example.com");
         opts.max_items = 5;
         opts.paginated = true;
-        let result = build_feed_from_articles(articles, &opts);
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
 
         for (idx, page) in result.pages.iter().enumerate() {
             let ext_map = page.channel.extensions();
@@ -569,7 +1218,7 @@ mod tests {
                 .find(|l| l.attrs().get("rel").map(String::as_str) == Some("self"))
                 .expect("rel=self link");
             let href = self_link.attrs().get("href").unwrap();
-            let expected_filename = rss_filename(idx);
+            let expected_filename = RssFormat.file_name(None, idx);
             assert!(
                 href.ends_with(&expected_filename),
                 "page {idx} self link '{href}' should end with '{expected_filename}'"
@@ -585,7 +1234,7 @@ mod tests {
         let mut opts = default_opts("https://example.com");
         opts.max_items = 5;
         opts.paginated = true;
-        let result = build_feed_from_articles(articles, &opts);
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
 
         let ext_map = result.pages[0].channel.extensions();
         let links = ext_map["atom"]["link"].as_slice();
@@ -605,7 +1254,7 @@ mod tests {
         let mut opts = default_opts("https://example.com");
         opts.max_items = 5;
         opts.paginated = true;
-        let result = build_feed_from_articles(articles, &opts);
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
 
         let last = result.pages.last().unwrap();
         let ext_map = last.channel.extensions();
@@ -626,16 +1275,216 @@ mod tests {
         let mut opts = default_opts("https://example.com");
         opts.max_items = 10;
         opts.paginated = true;
-        let result = build_feed_from_articles(articles, &opts);
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
         // 3 items fits within max_items=10 — should be a single page.
         assert_eq!(result.pages.len(), 1);
     }
 
+    // ── tag feeds ────────────────────────────────────────────────────────────
+
+    fn tagged_article(title: &str, tags: &[&str], categories: &[&str]) -> Article {
+        let mut a = make_article(title, &format!("{}.md", title.to_lowercase()), None, None);
+        a.fm.tags = tags.iter().map(ToString::to_string).collect();
+        a.fm.categories = categories.iter().map(ToString::to_string).collect();
+        a
+    }
+
+    #[test]
+    fn topic_slug_normalizes_names() {
+        assert_eq!(topic_slug("Rust"), "rust");
+        assert_eq!(topic_slug("  Release Notes! "), "release-notes");
+        assert_eq!(topic_slug("c++/wasm"), "c-wasm");
+        assert_eq!(topic_slug("???"), "");
+    }
+
+    #[test]
+    fn tag_feeds_disabled_by_default() {
+        let articles = vec![tagged_article("A", &["rust"], &[])];
+        let result = build_feed_from_articles(
+            articles,
+            &default_opts("https://example.com"),
+            &mut Diagnostics::new(),
+        );
+        assert!(result.tag_feeds.is_empty());
+        assert_eq!(result.dir, "");
+    }
+
+    #[test]
+    fn tag_feeds_one_result_per_tag_and_category() {
+        let articles = vec![
+            tagged_article("A", &["rust", "nix"], &["guides"]),
+            tagged_article("B", &["rust"], &[]),
+        ];
+        let mut opts = default_opts("https://example.com");
+        opts.tag_feeds = true;
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
+        let dirs: Vec<&str> = result.tag_feeds.iter().map(|r| r.dir.as_str()).collect();
+        assert_eq!(dirs, vec!["categories/guides", "tags/nix", "tags/rust"]);
+
+        let rust = &result.tag_feeds[2];
+        assert_eq!(rust.pages[0].filename, "tags/rust/rss.xml");
+        let channel = &rust.pages[0].channel;
+        assert_eq!(channel.title(), "Test Blog - rust");
+        assert_eq!(channel.items().len(), 2);
+        let self_href = channel.extensions()["atom"]["link"][0].attrs()["href"].clone();
+        assert_eq!(self_href, "https://example.com/tags/rust/rss.xml");
+    }
+
+    #[test]
+    fn output_dir_prefixes_pages_and_topic_feeds() {
+        let articles = vec![tagged_article("A", &["rust"], &[])];
+        let mut opts = default_opts("https://example.com");
+        opts.tag_feeds = true;
+        opts.output_dir = "releases";
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
+        assert_eq!(result.dir, "releases");
+        assert_eq!(result.pages[0].filename, "releases/rss.xml");
+        assert_eq!(result.tag_feeds[0].dir, "releases/tags/rust");
+        assert_eq!(
+            result.tag_feeds[0].pages[0].filename,
+            "releases/tags/rust/rss.xml"
+        );
+    }
+
+    #[test]
+    fn output_stem_names_pages_and_topic_feeds() {
+        let articles = (0..3)
+            .map(|i| tagged_article(&format!("Post{i}"), &["rust"], &[]))
+            .collect();
+        let opts = FeedOptions {
+            tag_feeds: true,
+            paginated: true,
+            max_items: 2,
+            output_stem: Some("releases"),
+            ..default_opts("https://example.com")
+        };
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
+        assert_eq!(result.stem.as_deref(), Some("releases"));
+        assert_eq!(result.pages[0].filename, "releases.xml");
+        assert_eq!(result.pages[1].filename, "releases2.xml");
+        let next = result.pages[0]
+            .channel
+            .extensions()
+            .get("atom")
+            .and_then(|m| m.get("link"))
+            .unwrap()
+            .iter()
+            .find(|l| l.attrs().get("rel").map(String::as_str) == Some("next"))
+            .unwrap()
+            .attrs()["href"]
+            .clone();
+        assert_eq!(next, "https://example.com/releases2.xml");
+        assert_eq!(
+            result.tag_feeds[0].pages[0].filename,
+            "tags/rust/releases.xml"
+        );
+    }
+
+    #[test]
+    fn tag_feeds_skip_excluded_articles_and_paginate() {
+        let mut excluded = tagged_article("Hidden", &["rust"], &[]);
+        excluded.fm.feed = Some(FeedVisibility::Exclude);
+        let mut articles: Vec<Article> = (0..3)
+            .map(|i| tagged_article(&format!("Post{i}"), &["rust"], &[]))
+            .collect();
+        articles.push(excluded);
+        let mut opts = default_opts("https://example.com");
+        opts.tag_feeds = true;
+        opts.paginated = true;
+        opts.max_items = 2;
+        let result = build_feed_from_articles(articles, &opts, &mut Diagnostics::new());
+        let rust = &result.tag_feeds[0];
+        assert_eq!(rust.pages.len(), 2);
+        assert_eq!(rust.pages[1].filename, "tags/rust/rss2.xml");
+        assert_eq!(rust.pages[1].channel.items().len(), 1);
+    }
+
     #[test]
     fn build_feed_from_articles_empty_articles_returns_one_empty_page() {
         let opts = default_opts("https://example.com");
-        let result = build_feed_from_articles(vec![], &opts);
+        let result = build_feed_from_articles(vec![], &opts, &mut Diagnostics::new());
         assert_eq!(result.pages.len(), 1);
         assert_eq!(result.pages[0].channel.items().len(), 0);
     }
+
+    // ── Diagnostics ───────────────────────────────────────────────────────────
+
+    #[test]
+    fn build_records_chapter_diagnostics() {
+        let mut undated = make_article("Undated", "undated.md", None, None);
+        undated.fm.authors = vec![Person::named("Jane")];
+        // Jane is only reported once, for the first chapter listing them.
+        let mut dated = make_article("Dated", "dir/README.md", Some("2024-01-01T00:00:00Z"), None);
+        dated.fm.authors = vec![Person::named("Jane")];
+        let index = make_article("Index", "dir/index.md", Some("2024-01-02T00:00:00Z"), None);
+        let mut empty = make_article("Empty", "empty.md", Some("2024-01-03T00:00:00Z"), None);
+        empty.content = String::new();
+        let hidden = make_article("Hidden", "hidden.md", None, Some(FeedVisibility::Exclude));
+
+        let mut diagnostics = Diagnostics::new();
+        let _ = build_feed_from_articles(
+            vec![undated, dated, index, empty, hidden],
+            &default_opts("https://example.com"),
+            &mut diagnostics,
+        );
+
+        let found: Vec<(&str, DiagnosticKind)> = diagnostics
+            .iter()
+            .map(|d| (d.path.as_str(), d.kind))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("undated.md", DiagnosticKind::MissingDate),
+                ("undated.md", DiagnosticKind::MissingAuthorEmail),
+                ("dir/index.md", DiagnosticKind::DuplicateLink),
+                ("empty.md", DiagnosticKind::EmptyPreview),
+            ]
+        );
+    }
+
+    #[test]
+    fn build_feed_expands_includes_relative_to_the_chapter() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("posts")).unwrap();
+        std::fs::write(
+            dir.path().join("posts/hello.md"),
+            "---\ntitle: Hello\ndate: 2024-01-01T00:00:00Z\n---\n\n\
+             {{#title Hello there}}\n\
+             Intro paragraph that is long enough to become the preview text.\n\n\
+             ```rust\n{{#include demo.rs:main}}\n```\n\n{{#include gone.rs}}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("posts/demo.rs"),
+            "// ANCHOR: main\nfn main() {}\n// ANCHOR_END: main\n",
+        )
+        .unwrap();
+
+        let mut opts = default_opts("https://example.com");
+        opts.full_preview = true;
+        let mut diagnostics = Diagnostics::new();
+        let result = build_feed(dir.path(), &opts, &DateSource::Mtime, &mut diagnostics).unwrap();
+
+        let html = &result.pages[0].feed.entries[0].content_html;
+        assert!(html.contains("fn main() {}"), "{html}");
+        assert!(!html.contains("{{#"), "{html}");
+        let found: Vec<DiagnosticKind> = diagnostics.iter().map(|d| d.kind).collect();
+        assert_eq!(found, vec![DiagnosticKind::UnresolvedInclude]);
+    }
+
+    #[test]
+    fn book_json_articles_keep_directive_text() {
+        // mdBook has already expanded these; what's left was escaped.
+        let mut article = make_article("Docs", "docs.md", Some("2024-01-01T00:00:00Z"), None);
+        article.content = "Write `{{#include x.rs}}` to include a file.\n".to_string();
+        let mut opts = default_opts("https://example.com");
+        opts.full_preview = true;
+        let mut diagnostics = Diagnostics::new();
+        let result = build_feed_from_articles(vec![article], &opts, &mut diagnostics);
+
+        let html = &result.pages[0].feed.entries[0].content_html;
+        assert!(html.contains("<code>{{#include x.rs}}</code>"), "{html}");
+        assert!(diagnostics.is_empty());
+    }
 }
@@ -0,0 +1,294 @@
+//! Output formats and the registry that renders every page in each of them.
+//!
+//! A [`FeedFormat`] decides how a page file is named, which URLs link the
+//! pages of one feed together, and how a [`Feed`] page is serialized. RSS is
+//! always available as [`RssFormat`]; Atom and JSON Feed are behind their
+//! cargo features. Downstream crates add their own format by implementing
+//! the trait and registering it in a [`FormatRegistry`].
+
+use crate::error::Result;
+use crate::feed::{BuildResult, feed_to_rss, page_path};
+use crate::model::Feed;
+
+/// URLs of a page and of its neighbours in the same feed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageLinks {
+    /// Where this page is published.
+    pub self_url: String,
+    /// The next (older) page, unless this is the last one.
+    pub next_url: Option<String>,
+    /// The previous (newer) page, unless this is the first one.
+    pub prev_url: Option<String>,
+}
+
+/// One output format, such as RSS 2.0 or Atom 1.0.
+pub trait FeedFormat {
+    /// Name used in log messages, e.g. `"RSS"`.
+    fn name(&self) -> &'static str;
+
+    /// File name of page `page_idx` (zero-based), e.g. `rss.xml` for the
+    /// first page and `rss2.xml` for the second. A `stem` (a section feed's
+    /// `output`) replaces the format's own name: `releases.xml`, and an
+    /// extension that tells the formats apart, such as `releases.atom`.
+    fn file_name(&self, stem: Option<&str>, page_idx: usize) -> String;
+
+    /// URLs for page `page_idx` of `total_pages`, published under `dir`
+    /// (relative to `base_url`, `""` for the site root) with file name
+    /// `stem`.
+    ///
+    /// The default links pages through [`FeedFormat::file_name`]; override
+    /// it for a different scheme.
+    fn page_links(
+        &self,
+        base_url: &str,
+        dir: &str,
+        stem: Option<&str>,
+        page_idx: usize,
+        total_pages: usize,
+    ) -> PageLinks {
+        let base_url = base_url.trim_end_matches('/');
+        let url = |idx: usize| format!("{base_url}/{}", page_path(dir, &self.file_name(stem, idx)));
+        PageLinks {
+            self_url: url(page_idx),
+            next_url: (page_idx + 1 < total_pages).then(|| url(page_idx + 1)),
+            prev_url: (page_idx > 0).then(|| url(page_idx - 1)),
+        }
+    }
+
+    /// Serialize one page.
+    ///
+    /// # Errors
+    /// Returns [`crate::FeedError::Render`] if the page can't be serialized.
+    fn render(&self, feed: &Feed, links: &PageLinks) -> Result<String>;
+}
+
+/// RSS 2.0: `rss.xml`, `rss2.xml`, ... (`<stem>.xml`) with `atom:link`
+/// pagination.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RssFormat;
+
+impl FeedFormat for RssFormat {
+    fn name(&self) -> &'static str {
+        "RSS"
+    }
+
+    fn file_name(&self, stem: Option<&str>, page_idx: usize) -> String {
+        numbered_file(stem.unwrap_or("rss"), "xml", page_idx)
+    }
+
+    fn render(&self, feed: &Feed, links: &PageLinks) -> Result<String> {
+        Ok(feed_to_rss(
+            feed,
+            &links.self_url,
+            links.next_url.as_deref(),
+            links.prev_url.as_deref(),
+        )
+        .to_string())
+    }
+}
+
+/// `{stem}.{ext}` for the first page, `{stem}{n}.{ext}` for page `n` after.
+#[must_use]
+pub fn numbered_file(stem: &str, ext: &str, page_idx: usize) -> String {
+    if page_idx == 0 {
+        format!("{stem}.{ext}")
+    } else {
+        format!("{stem}{}.{ext}", page_idx + 1)
+    }
+}
+
+/// A page rendered by a [`FormatRegistry`], ready to be written.
+#[derive(Debug, Clone)]
+pub struct RenderedPage {
+    /// [`FeedFormat::name`] of the format that produced it.
+    pub format: &'static str,
+    /// Path relative to the output root, e.g. `tags/rust/atom2.xml`.
+    pub path: String,
+    /// Where the page will be published.
+    pub self_url: String,
+    pub content: String,
+}
+
+/// The formats to write, in order.
+#[derive(Default)]
+pub struct FormatRegistry {
+    formats: Vec<Box<dyn FeedFormat>>,
+}
+
+impl FormatRegistry {
+    /// An empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a format; pages are rendered in registration order.
+    pub fn register(&mut self, format: impl FeedFormat + 'static) {
+        self.formats.push(Box::new(format));
+    }
+
+    /// Registered formats, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn FeedFormat> {
+        self.formats.iter().map(AsRef::as_ref)
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.formats.is_empty()
+    }
+
+    /// Render every page of `result` in every registered format.
+    ///
+    /// Topic feeds in [`BuildResult::tag_feeds`] are not included; render
+    /// them separately.
+    ///
+    /// # Errors
+    /// Returns the first error from [`FeedFormat::render`].
+    pub fn render(&self, result: &BuildResult, site_url: &str) -> Result<Vec<RenderedPage>> {
+        let total = result.pages.len();
+        let mut rendered = Vec::with_capacity(total * self.formats.len());
+        for format in self.iter() {
+            for (page_idx, page) in result.pages.iter().enumerate() {
+                let stem = result.stem.as_deref();
+                let links = format.page_links(site_url, &result.dir, stem, page_idx, total);
+                rendered.push(RenderedPage {
+                    format: format.name(),
+                    path: page_path(&result.dir, &format.file_name(stem, page_idx)),
+                    content: format.render(&page.feed, &links)?,
+                    self_url: links.self_url,
+                });
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::Diagnostics;
+    use crate::feed::{FeedOptions, build_feed_from_articles};
+    use crate::frontmatter::parse_frontmatter_lenient;
+    use crate::{Article, FeedError};
+
+    /// A downstream format: one plain-text line per entry.
+    struct TextFormat;
+
+    impl FeedFormat for TextFormat {
+        fn name(&self) -> &'static str {
+            "text"
+        }
+        fn file_name(&self, stem: Option<&str>, page_idx: usize) -> String {
+            format!("{}-{page_idx}.txt", stem.unwrap_or("feed"))
+        }
+        fn render(&self, feed: &Feed, links: &PageLinks) -> Result<String> {
+            if feed.entries.is_empty() {
+                return Err(FeedError::Render {
+                    format: "text".to_string(),
+                    message: "nothing to list".to_string(),
+                });
+            }
+            let mut out = format!("{} next={:?}\n", links.self_url, links.next_url);
+            for entry in &feed.entries {
+                out.push_str(&entry.title);
+                out.push('\n');
+            }
+            Ok(out)
+        }
+    }
+
+    fn result(count: usize, dir: &str) -> BuildResult {
+        let articles = (0..count)
+            .map(|i| {
+                let (fm, content) =
+                    parse_frontmatter_lenient(&format!("# Post {i}\n\nBody."), "", None);
+                Article {
+                    fm,
+                    content,
+                    path: format!("{i}.md"),
+                }
+            })
+            .collect();
+        let opts = FeedOptions {
+            title: "Book",
+            description: "Desc",
+            max_items: 2,
+            paginated: true,
+            output_dir: dir,
+            ..FeedOptions::new("https://example.com/")
+        };
+        build_feed_from_articles(articles, &opts, &mut Diagnostics::new())
+    }
+
+    #[test]
+    fn numbered_file_names() {
+        assert_eq!(numbered_file("atom", "xml", 0), "atom.xml");
+        assert_eq!(numbered_file("feed", "json", 2), "feed3.json");
+    }
+
+    #[test]
+    fn default_page_links_follow_file_names() {
+        let links = RssFormat.page_links("https://example.com/", "tags/rust", None, 1, 3);
+        assert_eq!(links.self_url, "https://example.com/tags/rust/rss2.xml");
+        assert_eq!(
+            links.next_url.as_deref(),
+            Some("https://example.com/tags/rust/rss3.xml")
+        );
+        assert_eq!(
+            links.prev_url.as_deref(),
+            Some("https://example.com/tags/rust/rss.xml")
+        );
+        let last = RssFormat.page_links("https://example.com", "", None, 0, 1);
+        assert_eq!((last.next_url, last.prev_url), (None, None));
+    }
+
+    #[test]
+    fn stems_replace_format_names() {
+        assert_eq!(RssFormat.file_name(Some("releases"), 1), "releases2.xml");
+        let links = RssFormat.page_links("https://example.com", "", Some("releases"), 0, 2);
+        assert_eq!(
+            links.next_url.as_deref(),
+            Some("https://example.com/releases2.xml")
+        );
+    }
+
+    #[test]
+    fn registry_renders_every_page_in_every_format() {
+        let mut registry = FormatRegistry::new();
+        registry.register(RssFormat);
+        registry.register(TextFormat);
+        let pages = registry
+            .render(&result(3, "blog"), "https://example.com")
+            .unwrap();
+        let paths: Vec<&str> = pages.iter().map(|p| p.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "blog/rss.xml",
+                "blog/rss2.xml",
+                "blog/feed-0.txt",
+                "blog/feed-1.txt"
+            ]
+        );
+        // The built-in RSS rendering matches the channel on the page.
+        let built = result(3, "blog");
+        assert_eq!(pages[0].content, built.pages[0].channel.to_string());
+        assert!(
+            pages[2]
+                .content
+                .starts_with("https://example.com/blog/feed-0.txt next=Some(")
+        );
+        assert_eq!(pages[3].self_url, "https://example.com/blog/feed-1.txt");
+    }
+
+    #[test]
+    fn registry_propagates_render_errors() {
+        let mut registry = FormatRegistry::new();
+        registry.register(TextFormat);
+        let err = registry
+            .render(&result(0, ""), "https://example.com")
+            .unwrap_err();
+        assert!(matches!(err, FeedError::Render { .. }), "{err:?}");
+    }
+}
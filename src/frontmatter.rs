@@ -1,8 +1,15 @@
 //! YAML frontmatter parsing for mdBook chapters.
 
+use std::path::Path;
+
 use chrono::{DateTime, NaiveDate, TimeZone, Utc};
 use serde::{Deserialize, Deserializer};
 
+use crate::diagnostics::{DiagnosticKind, Diagnostics};
+use crate::error::FeedError;
+use crate::model::Person;
+use crate::preview::{PreviewOverrides, PreviewUnit};
+
 /// Per-chapter feed inclusion control, set via the `feed` frontmatter key.
 ///
 /// ```yaml
@@ -21,32 +28,86 @@ pub enum FeedVisibility {
     Exclude,
 }
 
-/// Parse front-matter date formats (RFC3339 or `YYYY-MM-DD`).
-pub fn deserialize_date<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+/// Parse a frontmatter date: RFC3339 or `YYYY-MM-DD` (midnight UTC).
+fn parse_date(date_str: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(date_str) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    let nd = NaiveDate::parse_from_str(date_str, "%Y-%m-%d").ok()?;
+    // NaiveDate::and_hms_opt(0, 0, 0) only fails for an invalid hms,
+    // which 0,0,0 never is, so this expect documents an invariant
+    // rather than a real failure mode.
+    let midnight = nd
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is always a valid time");
+    Some(Utc.from_utc_datetime(&midnight))
+}
+
+/// A frontmatter value given either as a YAML list or a comma-separated
+/// string.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrList {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Parse a `tags:`/`categories:` value.
+///
+/// Accepts `[a, b]`, a YAML block list, or `"a, b"`. Entries are trimmed and
+/// empty entries are dropped.
+pub fn deserialize_string_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let s: Option<String> = Option::deserialize(deserializer)?;
-    let Some(date_str) = s else {
-        return Ok(None);
+    let raw: Option<StringOrList> = Option::deserialize(deserializer)?;
+    let items = match raw {
+        None => Vec::new(),
+        Some(StringOrList::One(s)) => s.split(',').map(str::to_string).collect(),
+        Some(StringOrList::Many(v)) => v,
     };
+    Ok(items
+        .into_iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect())
+}
 
-    if let Ok(dt) = DateTime::parse_from_rfc3339(&date_str) {
-        return Ok(Some(dt.with_timezone(&Utc)));
-    }
-    if let Ok(nd) = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
-        // NaiveDate::and_hms_opt(0, 0, 0) only fails for an invalid hms,
-        // which 0,0,0 never is, so this expect documents an invariant
-        // rather than a real failure mode.
-        let midnight = nd
-            .and_hms_opt(0, 0, 0)
-            .expect("midnight is always a valid time");
-        return Ok(Some(Utc.from_utc_datetime(&midnight)));
-    }
+/// One `author` entry: a name (or author registry id), or an object with
+/// `name`, `email`, `url` and `avatar`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AuthorValue {
+    Name(String),
+    Person(Person),
+}
+
+/// An `author` value: one entry or a list of them.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AuthorField {
+    One(AuthorValue),
+    Many(Vec<AuthorValue>),
+}
 
-    Err(serde::de::Error::custom(format!(
-        "invalid date '{date_str}': expected RFC3339 or YYYY-MM-DD"
-    )))
+/// Parse an `author:` value into people, dropping entries without a name.
+fn deserialize_authors<'de, D>(deserializer: D) -> Result<Vec<Person>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let values = match Option::<AuthorField>::deserialize(deserializer)? {
+        None => Vec::new(),
+        Some(AuthorField::One(value)) => vec![value],
+        Some(AuthorField::Many(values)) => values,
+    };
+    Ok(values
+        .into_iter()
+        .map(|value| match value {
+            AuthorValue::Name(name) => Person::named(name.trim()),
+            AuthorValue::Person(person) => person,
+        })
+        .filter(|person| !person.name.trim().is_empty())
+        .collect())
 }
 
 /// Raw deserialization target. `title` is optional so that a chapter with
@@ -54,12 +115,28 @@ where
 #[derive(Debug, Deserialize, Clone)]
 struct RawFrontmatter {
     title: Option<String>,
-    #[serde(deserialize_with = "deserialize_date", default)]
-    date: Option<DateTime<Utc>>,
-    author: Option<String>,
+    /// Kept as a string so an invalid date can be reported with its
+    /// position; see [`parse_date`].
+    date: Option<String>,
+    /// Same format as `date`.
+    updated: Option<String>,
+    #[serde(alias = "authors", deserialize_with = "deserialize_authors", default)]
+    author: Vec<Person>,
     description: Option<String>,
     #[serde(default)]
     feed: Option<FeedVisibility>,
+    #[serde(deserialize_with = "deserialize_string_list", default)]
+    tags: Vec<String>,
+    #[serde(deserialize_with = "deserialize_string_list", default)]
+    categories: Vec<String>,
+    #[serde(rename = "preview-paragraphs")]
+    preview_paragraphs: Option<usize>,
+    #[serde(rename = "preview-max-chars")]
+    preview_max_chars: Option<usize>,
+    #[serde(rename = "preview-min-body-chars")]
+    preview_min_body_chars: Option<usize>,
+    #[serde(rename = "preview-unit")]
+    preview_unit: Option<PreviewUnit>,
 }
 
 /// Parsed YAML frontmatter for a single chapter.
@@ -67,19 +144,37 @@ struct RawFrontmatter {
 /// Fields are used for feed metadata:
 /// - `title`: item title shown in the feed.
 /// - `date`: publish date for sorting and `pubDate` (RFC3339 or `YYYY-MM-DD`).
-/// - `author`: optional item author.
+/// - `updated`: last revision date, same format as `date`.
+/// - `author` (or `authors`): a name, an author registry id, an object
+///   `{name, email, url, avatar}`, or a list of these.
 /// - `description`: optional summary/preview override.
 /// - `feed`: per-chapter inclusion override (`include` or `exclude`).
+/// - `tags` / `categories`: topic labels, as a list or comma-separated string.
+/// - `preview-paragraphs`, `preview-max-chars`, `preview-min-body-chars`,
+///   `preview-unit`: preview sizing for this chapter, as in `book.toml`.
 #[derive(Debug, Clone)]
 pub struct FrontMatter {
     pub title: String,
     pub date: Option<DateTime<Utc>>,
-    pub author: Option<String>,
+    /// Last revision date (`updated:` key), falling back to the last commit
+    /// with [`crate::DateSource::Git`]. Becomes Atom `<updated>`, JSON Feed
+    /// `date_modified` and RSS `atom:updated`/`dcterms:modified`.
+    pub updated: Option<DateTime<Utc>>,
+    /// Item authors, in order. Plain names may still be registry ids; they
+    /// are resolved against [`crate::FeedOptions::author_registry`] when the
+    /// feed is built.
+    pub authors: Vec<Person>,
     /// User-supplied summary, used as a fallback preview source.
     pub description: Option<String>,
     /// Per-chapter feed inclusion override. When absent, the chapter follows
     /// the book-level `default-behavior` (`include-all` by default).
     pub feed: Option<FeedVisibility>,
+    /// Free-form topic tags (`tags:` key).
+    pub tags: Vec<String>,
+    /// Broader groupings (`categories:` key).
+    pub categories: Vec<String>,
+    /// Overrides for [`crate::FeedOptions::preview`].
+    pub preview: PreviewOverrides,
 }
 
 /// Extract the text of the first `# Heading` in a Markdown body.
@@ -158,60 +253,197 @@ fn split_frontmatter(raw: &str) -> (Option<String>, String) {
     (Some(yaml), body)
 }
 
-/// Parse frontmatter and body from raw Markdown.
+/// Frontmatter for a chapter with no (usable) YAML block.
+fn frontmatter_without_yaml(
+    body: &str,
+    title_hint: &str,
+    fallback_date: Option<DateTime<Utc>>,
+) -> FrontMatter {
+    FrontMatter {
+        title: resolve_title(None, body, title_hint),
+        date: fallback_date,
+        updated: None,
+        authors: Vec::new(),
+        description: None,
+        feed: None,
+        tags: Vec::new(),
+        categories: Vec::new(),
+        preview: PreviewOverrides::default(),
+    }
+}
+
+/// Build a [`FeedError::Frontmatter`] from a 1-based position inside the
+/// YAML block. The block starts on line 2, right after the opening `---`.
+fn frontmatter_error(path: &Path, (line, column): (usize, usize), message: String) -> FeedError {
+    FeedError::Frontmatter {
+        path: path.to_path_buf(),
+        line: line + 1,
+        column,
+        message,
+    }
+}
+
+/// Convert a YAML parse error, dropping `yaml_serde`'s own " at line N column
+/// M" suffix since it counts from the start of the block, not the file.
+fn yaml_error(path: &Path, err: &yaml_serde::Error) -> FeedError {
+    let mut message = err.to_string();
+    if let Some(idx) = message.rfind(" at line ") {
+        message.truncate(idx);
+    }
+    let position = err
+        .location()
+        .map_or((0, 1), |loc| (loc.line(), loc.column()));
+    frontmatter_error(path, position, message)
+}
+
+/// 1-based line and column of the value of top-level `key` in `yaml`.
+fn value_position(yaml: &str, key: &str) -> Option<(usize, usize)> {
+    yaml.lines().enumerate().find_map(|(idx, line)| {
+        let value = line.strip_prefix(key)?.strip_prefix(':')?;
+        let padding = value.len() - value.trim_start().len();
+        Some((idx + 1, key.len() + 1 + padding + 1))
+    })
+}
+
+/// Parse frontmatter and body from raw Markdown, failing on invalid YAML.
 ///
 /// Calls [`split_frontmatter`] to extract the YAML block, then interprets
-/// it into a [`FrontMatter`] struct. If no frontmatter is present or parsing
-/// fails, falls back gracefully using `title_hint` and `fallback_date`.
-#[allow(clippy::option_if_let_else)]
-#[must_use]
-pub fn parse_frontmatter(
+/// it into a [`FrontMatter`] struct. A missing or empty block is not an
+/// error: the title falls back to the first `# Heading` or `title_hint`,
+/// and the date to `fallback_date`.
+///
+/// # Errors
+/// Returns [`FeedError::Frontmatter`] when the YAML block cannot be parsed
+/// (malformed YAML, an invalid `date`, an unknown `feed` value, ...). `path`
+/// is only used to label the error.
+pub fn try_parse_frontmatter(
     raw: &str,
+    path: &Path,
     title_hint: &str,
     fallback_date: Option<DateTime<Utc>>,
-    strict: bool,
-) -> (FrontMatter, String) {
+) -> crate::Result<(FrontMatter, String)> {
     let (yaml_opt, body) = split_frontmatter(raw);
-    let yaml_opt = yaml_opt.filter(|y| !y.trim().is_empty());
-
-    let fm = match yaml_opt {
-        None => FrontMatter {
-            title: resolve_title(None, &body, title_hint),
-            date: fallback_date,
-            author: None,
-            description: None,
-            feed: None,
-        },
-        Some(yaml) => match yaml_serde::from_str::<RawFrontmatter>(&yaml) {
-            Ok(raw_fm) => FrontMatter {
-                title: resolve_title(raw_fm.title, &body, title_hint),
-                date: raw_fm.date.or(fallback_date),
-                author: raw_fm.author,
-                description: raw_fm.description,
-                feed: raw_fm.feed,
-            },
-            Err(e) => {
-                let msg = format!(
-                    "mdbook-rss-feed: failed to parse frontmatter for \
-                     '{title_hint}': {e}"
-                );
-                if strict {
-                    eprintln!("error: {msg}");
-                    std::process::exit(1);
-                }
-                eprintln!("warning: {msg} (use strict = true to fail the build)");
-                FrontMatter {
-                    title: resolve_title(None, &body, title_hint),
-                    date: fallback_date,
-                    author: None,
-                    description: None,
-                    feed: None,
-                }
-            }
+    let Some(yaml) = yaml_opt.filter(|y| !y.trim().is_empty()) else {
+        let fm = frontmatter_without_yaml(&body, title_hint, fallback_date);
+        return Ok((fm, body));
+    };
+
+    let raw_fm = yaml_serde::from_str::<RawFrontmatter>(&yaml).map_err(|e| yaml_error(path, &e))?;
+    let date_key = |key: &str, value: Option<&str>| match value {
+        None => Ok(None),
+        Some(value) => parse_date(value).map(Some).ok_or_else(|| {
+            frontmatter_error(
+                path,
+                value_position(&yaml, key).unwrap_or((0, 1)),
+                format!("invalid {key} '{value}': expected RFC3339 or YYYY-MM-DD"),
+            )
+        }),
+    };
+    let date = date_key("date", raw_fm.date.as_deref())?;
+    let updated = date_key("updated", raw_fm.updated.as_deref())?;
+    let fm = FrontMatter {
+        title: resolve_title(raw_fm.title, &body, title_hint),
+        date: date.or(fallback_date),
+        updated,
+        authors: raw_fm.author,
+        description: raw_fm.description,
+        feed: raw_fm.feed,
+        tags: raw_fm.tags,
+        categories: raw_fm.categories,
+        preview: PreviewOverrides {
+            paragraphs: raw_fm.preview_paragraphs,
+            max_chars: raw_fm.preview_max_chars,
+            min_body_chars: raw_fm.preview_min_body_chars,
+            unit: raw_fm.preview_unit,
         },
     };
+    Ok((fm, body))
+}
+
+/// [`try_parse_frontmatter`], handing a parse error to `on_error` and
+/// falling back to defaults instead of failing.
+fn parse_frontmatter_or_else(
+    raw: &str,
+    path: &Path,
+    title_hint: &str,
+    fallback_date: Option<DateTime<Utc>>,
+    on_error: impl FnOnce(FeedError),
+) -> (FrontMatter, String) {
+    try_parse_frontmatter(raw, path, title_hint, fallback_date).unwrap_or_else(|e| {
+        on_error(e);
+        let (_, body) = split_frontmatter(raw);
+        let fm = frontmatter_without_yaml(&body, title_hint, fallback_date);
+        (fm, body)
+    })
+}
+
+/// Parse a chapter's frontmatter, failing only in strict mode. Otherwise a
+/// parse error is recorded in `diagnostics` under `rel_path`.
+pub(crate) fn parse_chapter_frontmatter(
+    raw: &str,
+    path: &Path,
+    rel_path: &str,
+    title_hint: &str,
+    fallback_date: Option<DateTime<Utc>>,
+    strict: bool,
+    diagnostics: &mut Diagnostics,
+) -> crate::Result<(FrontMatter, String)> {
+    if strict {
+        return try_parse_frontmatter(raw, path, title_hint, fallback_date);
+    }
+    Ok(parse_frontmatter_or_else(
+        raw,
+        path,
+        title_hint,
+        fallback_date,
+        |e| {
+            let message = match e {
+                FeedError::Frontmatter {
+                    line,
+                    column,
+                    message,
+                    ..
+                } => format!("line {line}, column {column}: {message}; using defaults"),
+                other => other.to_string(),
+            };
+            diagnostics.warn(rel_path, DiagnosticKind::InvalidFrontmatter, message);
+        },
+    ))
+}
 
-    (fm, body)
+/// Parse frontmatter and body from raw Markdown, never failing.
+///
+/// Lenient counterpart of [`try_parse_frontmatter`]: if the YAML block
+/// cannot be parsed, a warning is printed and the chapter falls back to
+/// `title_hint` and `fallback_date` as if it had no frontmatter.
+#[must_use]
+pub fn parse_frontmatter_lenient(
+    raw: &str,
+    title_hint: &str,
+    fallback_date: Option<DateTime<Utc>>,
+) -> (FrontMatter, String) {
+    parse_frontmatter_or_else(raw, Path::new(title_hint), title_hint, fallback_date, |e| {
+        eprintln!("warning: mdbook-rss-feed: {e} (use strict = true to fail the build)");
+    })
+}
+
+/// Parse frontmatter and body from raw Markdown.
+///
+/// This is [`parse_frontmatter_lenient`]. `strict` is ignored: the library
+/// no longer exits the process, so an invalid YAML block warns and falls
+/// back either way.
+#[deprecated(
+    since = "2.0.0",
+    note = "use `try_parse_frontmatter`, or `parse_frontmatter_lenient`"
+)]
+#[must_use]
+pub fn parse_frontmatter(
+    raw: &str,
+    title_hint: &str,
+    fallback_date: Option<DateTime<Utc>>,
+    _strict: bool,
+) -> (FrontMatter, String) {
+    parse_frontmatter_lenient(raw, title_hint, fallback_date)
 }
 
 #[allow(clippy::unwrap_used)]
@@ -280,12 +512,12 @@ mod tests {
         assert_eq!(result, "Real Title");
     }
 
-    // ── parse_frontmatter ─────────────────────────────────────────────────────
+    // ── parse_frontmatter_lenient ───────────────────────────────────────────
 
     #[test]
     fn parse_frontmatter_no_frontmatter_returns_whole_body() {
         let raw = "# Plain Chapter\n\nJust content, no frontmatter.";
-        let (fm, body) = parse_frontmatter(raw, "hint", None, false);
+        let (fm, body) = parse_frontmatter_lenient(raw, "hint", None);
         assert_eq!(fm.title, "Plain Chapter");
         assert!(body.contains("Just content"));
         assert!(body.contains("# Plain Chapter"));
@@ -294,9 +526,9 @@ mod tests {
     #[test]
     fn parse_frontmatter_full_yaml_block() {
         let raw = "---\ntitle: My Post\ndate: 2024-06-01\nauthor: Alice\ndescription: A summary.\n---\n\nBody content.";
-        let (fm, body) = parse_frontmatter(raw, "hint", None, false);
+        let (fm, body) = parse_frontmatter_lenient(raw, "hint", None);
         assert_eq!(fm.title, "My Post");
-        assert_eq!(fm.author.as_deref(), Some("Alice"));
+        assert_eq!(fm.authors, vec![Person::named("Alice")]);
         assert_eq!(fm.description.as_deref(), Some("A summary."));
         assert!(fm.date.is_some());
         assert!(body.contains("Body content."));
@@ -305,7 +537,7 @@ mod tests {
     #[test]
     fn parse_frontmatter_date_rfc3339() {
         let raw = "---\ndate: 2024-03-15T12:00:00Z\n---\n\nContent.";
-        let (fm, _) = parse_frontmatter(raw, "hint", None, false);
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", None);
         let date = fm.date.expect("should have parsed date");
         assert_eq!(date.format("%Y-%m-%d").to_string(), "2024-03-15");
     }
@@ -313,7 +545,7 @@ mod tests {
     #[test]
     fn parse_frontmatter_date_yyyy_mm_dd() {
         let raw = "---\ndate: 2023-11-30\n---\n\nContent.";
-        let (fm, _) = parse_frontmatter(raw, "hint", None, false);
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", None);
         let date = fm.date.expect("should have parsed date");
         assert_eq!(date.format("%Y-%m-%d").to_string(), "2023-11-30");
     }
@@ -321,14 +553,14 @@ mod tests {
     #[test]
     fn parse_frontmatter_title_from_h1_when_no_yaml_title() {
         let raw = "---\ndate: 2024-01-01\n---\n\n# Heading From Body\n\nContent.";
-        let (fm, _) = parse_frontmatter(raw, "hint", None, false);
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", None);
         assert_eq!(fm.title, "Heading From Body");
     }
 
     #[test]
     fn parse_frontmatter_title_hint_fallback() {
         let raw = "---\ndate: 2024-01-01\n---\n\nNo heading here.";
-        let (fm, _) = parse_frontmatter(raw, "my-chapter", None, false);
+        let (fm, _) = parse_frontmatter_lenient(raw, "my-chapter", None);
         assert_eq!(fm.title, "my-chapter");
     }
 
@@ -336,7 +568,7 @@ mod tests {
     fn parse_frontmatter_unclosed_delimiter_treated_as_body() {
         // Opening `---` but no closing one — entire file is body.
         let raw = "---\ntitle: Orphaned\ndate: 2024-01-01\n";
-        let (fm, body) = parse_frontmatter(raw, "fallback", None, false);
+        let (fm, body) = parse_frontmatter_lenient(raw, "fallback", None);
         // Title should come from the body (which contains the raw `---` and
         // yaml) or fall back to hint; importantly it should NOT crash.
         let _ = fm.title; // just check it doesn't panic
@@ -346,7 +578,7 @@ mod tests {
     #[test]
     fn parse_frontmatter_empty_yaml_block() {
         let raw = "---\n---\n\n# Body Heading\n\nContent.";
-        let (fm, body) = parse_frontmatter(raw, "hint", None, false);
+        let (fm, body) = parse_frontmatter_lenient(raw, "hint", None);
         // Empty YAML block => title from h1.
         assert_eq!(fm.title, "Body Heading");
         assert!(body.contains("Content."));
@@ -355,21 +587,21 @@ mod tests {
     #[test]
     fn parse_frontmatter_feed_include() {
         let raw = "---\ntitle: Included\nfeed: include\n---\n\nContent.";
-        let (fm, _) = parse_frontmatter(raw, "hint", None, false);
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", None);
         assert_eq!(fm.feed, Some(FeedVisibility::Include));
     }
 
     #[test]
     fn parse_frontmatter_feed_exclude() {
         let raw = "---\ntitle: Hidden\nfeed: exclude\n---\n\nContent.";
-        let (fm, _) = parse_frontmatter(raw, "hint", None, false);
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", None);
         assert_eq!(fm.feed, Some(FeedVisibility::Exclude));
     }
 
     #[test]
     fn parse_frontmatter_no_feed_key_is_none() {
         let raw = "---\ntitle: Normal\n---\n\nContent.";
-        let (fm, _) = parse_frontmatter(raw, "hint", None, false);
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", None);
         assert_eq!(fm.feed, None);
     }
 
@@ -378,7 +610,7 @@ mod tests {
         use chrono::TimeZone;
         let fallback = chrono::Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
         let raw = "---\ntitle: No Date\n---\n\nContent.";
-        let (fm, _) = parse_frontmatter(raw, "hint", Some(fallback), false);
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", Some(fallback));
         assert_eq!(fm.date, Some(fallback));
     }
 
@@ -387,7 +619,7 @@ mod tests {
         use chrono::TimeZone;
         let fallback = chrono::Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
         let raw = "---\ntitle: Has Date\ndate: 2024-06-15\n---\n\nContent.";
-        let (fm, _) = parse_frontmatter(raw, "hint", Some(fallback), false);
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", Some(fallback));
         let date = fm.date.expect("should have a date");
         // Should be 2024-06-15, not the 2020 fallback.
         assert_eq!(date.format("%Y-%m-%d").to_string(), "2024-06-15");
@@ -397,18 +629,149 @@ mod tests {
     fn parse_frontmatter_invalid_yaml_non_strict_warns_and_continues() {
         // Malformed YAML (unclosed bracket) - should not panic in non-strict mode.
         let raw = "---\ntitle: [unclosed\n---\n\n# Heading\n\nContent.";
-        let (fm, body) = parse_frontmatter(raw, "fallback", None, false);
+        let (fm, body) = parse_frontmatter_lenient(raw, "fallback", None);
         // Title falls back to h1 or hint.
         assert_ne!(fm.title, "");
         assert!(body.contains("Content."));
     }
 
-    // ── deserialize_date ─────────────────────────────────────────────────────
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_parse_frontmatter_keeps_its_signature() {
+        let raw = "---\ntitle: [unclosed\n---\n\n# Heading\n\nContent.";
+        let (lenient_fm, lenient_body) = parse_frontmatter_lenient(raw, "fallback", None);
+        for strict in [false, true] {
+            let (fm, body) = parse_frontmatter(raw, "fallback", None, strict);
+            assert_eq!(fm.title, lenient_fm.title);
+            assert_eq!(body, lenient_body);
+        }
+    }
+
+    #[test]
+    fn try_parse_frontmatter_reports_file_location() {
+        let raw = "---\ntitle: Fine\ndate: not-a-date\n---\n\nContent.";
+        let err = try_parse_frontmatter(raw, Path::new("posts/bad.md"), "hint", None).unwrap_err();
+        let FeedError::Frontmatter {
+            path,
+            line,
+            column,
+            message,
+        } = err
+        else {
+            panic!("expected a frontmatter error, got {err:?}");
+        };
+        assert_eq!(path, Path::new("posts/bad.md"));
+        // Line 3 of the file: `---` is line 1, `title:` line 2.
+        assert_eq!((line, column), (3, 7));
+        assert!(message.contains("invalid date 'not-a-date'"), "{message}");
+        assert!(!message.contains("at line"), "{message}");
+    }
+
+    #[test]
+    fn parse_frontmatter_updated_date() {
+        let raw = "---\ndate: 2024-01-15\nupdated: 2024-03-01T12:00:00Z\n---\n\nContent.";
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", None);
+        assert_eq!(fm.date.unwrap().to_rfc3339(), "2024-01-15T00:00:00+00:00");
+        assert_eq!(
+            fm.updated.unwrap().to_rfc3339(),
+            "2024-03-01T12:00:00+00:00"
+        );
+
+        let raw = "---\ntitle: T\nupdated: soon\n---\n";
+        let err = try_parse_frontmatter(raw, Path::new("a.md"), "hint", None).unwrap_err();
+        assert!(
+            err.to_string()
+                .ends_with("a.md:3:10: invalid updated 'soon': expected RFC3339 or YYYY-MM-DD"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn parse_frontmatter_structured_authors() {
+        let raw = "---\nauthor:\n  - alice\n  - name: Bob\n    email: bob@example.com\n    \
+                   url: https://bob.example\n    avatar: https://bob.example/a.png\n  - ''\n---\n";
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", None);
+        assert_eq!(
+            fm.authors,
+            vec![
+                Person::named("alice"),
+                Person {
+                    name: "Bob".to_string(),
+                    email: Some("bob@example.com".to_string()),
+                    url: Some("https://bob.example".to_string()),
+                    avatar: Some("https://bob.example/a.png".to_string()),
+                },
+            ]
+        );
+
+        let raw = "---\nauthors: {name: Carol, email: carol@example.com}\n---\n";
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", None);
+        assert_eq!(fm.authors[0].email.as_deref(), Some("carol@example.com"));
+    }
+
+    #[test]
+    fn parse_frontmatter_preview_overrides() {
+        let raw = "---\npreview-paragraphs: 1\npreview-max-chars: 40\npreview-unit: words\n---\n";
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", None);
+        assert_eq!(
+            fm.preview,
+            PreviewOverrides {
+                paragraphs: Some(1),
+                max_chars: Some(40),
+                min_body_chars: None,
+                unit: Some(PreviewUnit::Words),
+            }
+        );
+
+        let err = try_parse_frontmatter(
+            "---\npreview-unit: lines\n---\n",
+            Path::new("c.md"),
+            "hint",
+            None,
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, FeedError::Frontmatter { line: 2, .. }),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn try_parse_frontmatter_accepts_missing_block() {
+        let (fm, body) =
+            try_parse_frontmatter("# Plain\n\nBody.", Path::new("plain.md"), "hint", None).unwrap();
+        assert_eq!(fm.title, "Plain");
+        assert!(body.contains("Body."));
+    }
+
+    #[test]
+    fn parse_frontmatter_tags_as_list() {
+        let raw = "---\ntitle: Tagged\ntags: [rust, nix]\n---\n\nContent.";
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", None);
+        assert_eq!(fm.tags, vec!["rust", "nix"]);
+        assert!(fm.categories.is_empty());
+    }
+
+    #[test]
+    fn parse_frontmatter_categories_as_comma_string() {
+        let raw = "---\ntitle: Tagged\ncategories: \"guides, ,  release notes\"\n---\n\nContent.";
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", None);
+        assert_eq!(fm.categories, vec!["guides", "release notes"]);
+    }
+
+    #[test]
+    fn parse_frontmatter_tags_as_block_list() {
+        let raw = "---\ntags:\n  - rust\n  - mdbook\n---\n\nContent.";
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", None);
+        assert_eq!(fm.tags, vec!["rust", "mdbook"]);
+    }
+
+    // ── date formats ─────────────────────────────────────────────────────────
 
     #[test]
     fn deserialize_date_accepts_rfc3339() {
         let raw = "---\ndate: 2024-07-04T00:00:00Z\n---\n\nContent.";
-        let (fm, _) = parse_frontmatter(raw, "hint", None, false);
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", None);
         assert!(fm.date.is_some());
         assert_eq!(
             fm.date.unwrap().format("%Y-%m-%d").to_string(),
@@ -419,7 +782,7 @@ mod tests {
     #[test]
     fn deserialize_date_accepts_naive_date() {
         let raw = "---\ndate: 2024-07-04\n---\n\nContent.";
-        let (fm, _) = parse_frontmatter(raw, "hint", None, false);
+        let (fm, _) = parse_frontmatter_lenient(raw, "hint", None);
         assert!(fm.date.is_some());
         assert_eq!(
             fm.date.unwrap().format("%Y-%m-%d").to_string(),
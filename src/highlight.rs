@@ -0,0 +1,116 @@
+//! Syntax highlighting for code blocks.
+//!
+//! Enabled by the `highlight` cargo feature. Feed readers don't load the
+//! book's CSS, so fenced code blocks are highlighted with inline `style`
+//! attributes, using syntect's bundled syntaxes and themes.
+
+use std::sync::LazyLock;
+
+use pulldown_cmark::{CodeBlockKind, Event, Tag, TagEnd};
+use syntect::highlighting::ThemeSet;
+use syntect::html::highlighted_html_for_string;
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+
+/// Theme used when `highlight` is on without a `highlight-theme`.
+pub const DEFAULT_HIGHLIGHT_THEME: &str = "InspiredGitHub";
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEMES: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+/// Names of the bundled themes `highlight-theme` can choose from.
+#[must_use]
+pub fn highlight_themes() -> Vec<&'static str> {
+    THEMES.themes.keys().map(String::as_str).collect()
+}
+
+/// The syntax for a fenced block's info string (`rust,editable` → Rust).
+fn syntax_for(kind: &CodeBlockKind<'_>) -> Option<&'static SyntaxReference> {
+    let CodeBlockKind::Fenced(info) = kind else {
+        return None;
+    };
+    let language = info
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .find(|w| !w.is_empty())?;
+    SYNTAXES.find_syntax_by_token(language)
+}
+
+/// Replace every fenced code block in a known language with inline-styled
+/// HTML in `theme`. Blocks in other languages, and all of them when `theme`
+/// isn't one of [`highlight_themes`], are left as they are.
+pub(crate) fn highlight_code_blocks<'a>(events: Vec<Event<'a>>, theme: &str) -> Vec<Event<'a>> {
+    let Some(theme) = THEMES.themes.get(theme) else {
+        return events;
+    };
+    let mut out = Vec::with_capacity(events.len());
+    // The block being highlighted: its opening event, syntax and code.
+    let mut block: Option<(Event<'a>, &SyntaxReference, String)> = None;
+    for event in events {
+        match (event, &mut block) {
+            (Event::Start(Tag::CodeBlock(kind)), None) => match syntax_for(&kind) {
+                Some(syntax) => {
+                    block = Some((Event::Start(Tag::CodeBlock(kind)), syntax, String::new()));
+                }
+                None => out.push(Event::Start(Tag::CodeBlock(kind))),
+            },
+            (Event::Text(text), Some((_, _, code))) => code.push_str(&text),
+            (Event::End(TagEnd::CodeBlock), Some(_)) => {
+                let Some((start, syntax, code)) = block.take() else {
+                    continue;
+                };
+                match highlighted_html_for_string(&code, &SYNTAXES, syntax, theme) {
+                    Ok(html) => out.push(Event::Html(html.into())),
+                    Err(_) => out.extend([
+                        start,
+                        Event::Text(code.into()),
+                        Event::End(TagEnd::CodeBlock),
+                    ]),
+                }
+            }
+            (event, _) => out.push(event),
+        }
+    }
+    out
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{Parser, html};
+
+    fn render(md: &str, theme: &str) -> String {
+        let events = highlight_code_blocks(Parser::new(md).collect(), theme);
+        let mut out = String::new();
+        html::push_html(&mut out, events.into_iter());
+        out
+    }
+
+    #[test]
+    fn highlights_known_languages_with_inline_styles() {
+        let out = render(
+            "```rust,editable\nfn main() {}\n```\n\n```nosuchlang\nx\n```\n",
+            DEFAULT_HIGHLIGHT_THEME,
+        );
+        let (rust, other) = out.split_once("</pre>").unwrap();
+        assert!(rust.starts_with("<pre style=\"background-color:#"), "{out}");
+        assert!(
+            rust.contains("<span style=\"font-weight:bold;color:#"),
+            "{out}"
+        );
+        assert!(!rust.contains("class="), "{out}");
+        assert_eq!(
+            other,
+            "\n<pre><code class=\"language-nosuchlang\">x\n</code></pre>\n"
+        );
+    }
+
+    #[test]
+    fn unknown_theme_leaves_code_alone() {
+        let out = render("```rust\nfn main() {}\n```\n", "no-such-theme");
+        assert_eq!(
+            out,
+            "<pre><code class=\"language-rust\">fn main() {}\n</code></pre>\n"
+        );
+        assert!(highlight_themes().contains(&DEFAULT_HIGHLIGHT_THEME));
+    }
+}
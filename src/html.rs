@@ -0,0 +1,662 @@
+//! Rewriting rendered chapter HTML for feeds.
+//!
+//! Built on `lol_html`, a streaming tokenizer: only the elements and
+//! attributes we touch are re-serialized, everything else is copied through
+//! byte for byte.
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+use lol_html::html_content::Element;
+use lol_html::{RewriteStrSettings, doc_comments, element, rewrite_str};
+
+use crate::url::resolve_url;
+
+/// Attributes holding a single URL, on any element.
+const URL_ATTRIBUTES: &[&str] = &[
+    "href",
+    "src",
+    "poster",
+    "cite",
+    "action",
+    "formaction",
+    "background",
+    "data-src",
+];
+
+/// Attributes holding a comma-separated list of image candidates.
+const SRCSET_ATTRIBUTES: &[&str] = &["srcset", "data-srcset"];
+
+/// Tags kept by the default [`Allowlist`]: text, lists, tables, images and
+/// media.
+pub const DEFAULT_ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "audio",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "picture",
+    "pre",
+    "q",
+    "s",
+    "samp",
+    "small",
+    "source",
+    "span",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "tr",
+    "track",
+    "u",
+    "ul",
+    "var",
+    "video",
+];
+
+/// Attributes kept by the default [`Allowlist`], on any allowed tag.
+pub const DEFAULT_ALLOWED_ATTRIBUTES: &[&str] = &[
+    "align", "alt", "cite", "colspan", "controls", "datetime", "height", "href", "id", "kind",
+    "label", "lang", "open", "poster", "reversed", "rowspan", "sizes", "span", "src", "srclang",
+    "srcset", "start", "title", "type", "width",
+];
+
+/// MathML Core elements, added by [`Allowlist::with_mathml`].
+pub const MATHML_TAGS: &[&str] = &[
+    "annotation",
+    "math",
+    "merror",
+    "mfrac",
+    "mi",
+    "mmultiscripts",
+    "mn",
+    "mo",
+    "mover",
+    "mpadded",
+    "mphantom",
+    "mprescripts",
+    "mroot",
+    "mrow",
+    "ms",
+    "mspace",
+    "msqrt",
+    "mstyle",
+    "msub",
+    "msubsup",
+    "msup",
+    "mtable",
+    "mtd",
+    "mtext",
+    "mtr",
+    "munder",
+    "munderover",
+    "none",
+    "semantics",
+];
+
+/// MathML Core attributes, added by [`Allowlist::with_mathml`].
+pub const MATHML_ATTRIBUTES: &[&str] = &[
+    "accent",
+    "accentunder",
+    "columnspan",
+    "depth",
+    "display",
+    "displaystyle",
+    "encoding",
+    "fence",
+    "form",
+    "largeop",
+    "linethickness",
+    "lspace",
+    "mathvariant",
+    "maxsize",
+    "minsize",
+    "movablelimits",
+    "rspace",
+    "scriptlevel",
+    "separator",
+    "stretchy",
+    "symmetric",
+    "voffset",
+    "xmlns",
+];
+
+/// Elements removed together with their content when they aren't allowed;
+/// other elements that aren't allowed are unwrapped, keeping their text.
+const DROP_WITH_CONTENT: &[&str] = &[
+    "applet", "base", "button", "embed", "frame", "frameset", "head", "iframe", "input", "link",
+    "math", "meta", "noscript", "object", "script", "select", "style", "svg", "template",
+    "textarea", "title",
+];
+
+/// Properties a `style` attribute may set with
+/// [`Allowlist::with_highlight_styles`], even when `style` isn't allowed:
+/// enough for syntax highlighting, nothing that can move, hide or load
+/// anything.
+const PLAIN_STYLE_PROPERTIES: &[&str] = &[
+    "background-color",
+    "color",
+    "font-style",
+    "font-weight",
+    "text-decoration",
+];
+
+/// Which tags and attributes [`sanitize_html`] keeps.
+///
+/// Names are matched in lowercase. Event handlers (`on*`) are always
+/// removed, and so are URL attributes whose scheme isn't `http`, `https` or
+/// `mailto`, whatever the lists say.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Allowlist {
+    pub tags: BTreeSet<String>,
+    pub attributes: BTreeSet<String>,
+    /// Keep a `style` that only sets colors and font styles, as highlighted
+    /// code has, even when `style` isn't in `attributes`.
+    pub plain_styles: bool,
+    /// Keep `class="math math-inline"` and `class="math math-display"` on
+    /// `<code>`, as math rendered as code has, even when `class` isn't in
+    /// `attributes`.
+    pub math_classes: bool,
+}
+
+impl Default for Allowlist {
+    fn default() -> Self {
+        Self {
+            tags: DEFAULT_ALLOWED_TAGS
+                .iter()
+                .map(|t| (*t).to_string())
+                .collect(),
+            attributes: DEFAULT_ALLOWED_ATTRIBUTES
+                .iter()
+                .map(|a| (*a).to_string())
+                .collect(),
+            plain_styles: false,
+            math_classes: false,
+        }
+    }
+}
+
+impl Allowlist {
+    /// This allowlist plus [`MATHML_TAGS`] and [`MATHML_ATTRIBUTES`], for
+    /// content with math rendered as MathML.
+    #[must_use]
+    pub fn with_mathml(mut self) -> Self {
+        self.tags
+            .extend(MATHML_TAGS.iter().map(|t| (*t).to_string()));
+        self.attributes
+            .extend(MATHML_ATTRIBUTES.iter().map(|a| (*a).to_string()));
+        self
+    }
+
+    /// This allowlist keeping the inline colors and font styles of
+    /// highlighted code (see [`Allowlist::plain_styles`]).
+    #[must_use]
+    pub const fn with_highlight_styles(mut self) -> Self {
+        self.plain_styles = true;
+        self
+    }
+
+    /// This allowlist keeping the classes of math rendered as code (see
+    /// [`Allowlist::math_classes`]).
+    #[must_use]
+    pub const fn with_math_classes(mut self) -> Self {
+        self.math_classes = true;
+        self
+    }
+}
+
+/// Strip chapter HTML down to `allowlist`, so feed readers that render
+/// content unsanitized get no scripts, frames, styles or event handlers.
+///
+/// Disallowed `<script>`, `<style>`, `<iframe>`, form controls and the like
+/// are removed with their content; any other disallowed element is
+/// unwrapped. Comments are removed too.
+#[must_use]
+pub fn sanitize_html(html: &str, allowlist: &Allowlist) -> String {
+    let settings = RewriteStrSettings {
+        element_content_handlers: vec![element!("*", |el| {
+            sanitize_element(el, allowlist);
+            Ok(())
+        })],
+        document_content_handlers: vec![doc_comments!(|comment| {
+            comment.remove();
+            Ok(())
+        })],
+        ..RewriteStrSettings::new()
+    };
+    // Unlike `make_urls_absolute`, failing open would let the unsafe input
+    // through.
+    rewrite_str(html, settings).unwrap_or_default()
+}
+
+fn sanitize_element(el: &mut Element<'_, '_>, allowlist: &Allowlist) {
+    let tag = el.tag_name();
+    if !allowlist.tags.contains(&tag) {
+        if DROP_WITH_CONTENT.contains(&tag.as_str()) {
+            el.remove();
+        } else {
+            el.remove_and_keep_content();
+        }
+        return;
+    }
+    let rejected: Vec<String> = el
+        .attributes()
+        .iter()
+        .map(|attr| (attr.name(), attr.value()))
+        .filter(|(name, value)| {
+            let allowed = allowlist.attributes.contains(name)
+                || allowlist.plain_styles && name == "style" && is_plain_style(value)
+                || allowlist.math_classes
+                    && tag == "code"
+                    && name == "class"
+                    && is_math_class(value);
+            name.starts_with("on") || !allowed || is_url_attribute(name) && !has_safe_scheme(value)
+        })
+        .map(|(name, _)| name)
+        .collect();
+    for name in rejected {
+        el.remove_attribute(&name);
+    }
+}
+
+/// `true` for a declaration list using only [`PLAIN_STYLE_PROPERTIES`] with
+/// simple values (colors, keywords, numbers).
+fn is_plain_style(css: &str) -> bool {
+    css.split(';')
+        .map(str::trim)
+        .filter(|declaration| !declaration.is_empty())
+        .all(|declaration| {
+            declaration
+                .split_once(':')
+                .is_some_and(|(property, value)| {
+                    PLAIN_STYLE_PROPERTIES.contains(&property.trim().to_ascii_lowercase().as_str())
+                        && value.trim().chars().all(|c| {
+                            c.is_ascii_alphanumeric()
+                                || matches!(c, '#' | ' ' | '-' | '%' | '.' | ',')
+                        })
+                })
+        })
+}
+
+/// `true` for the classes [`MathMode::Code`](crate::MathMode::Code) puts on
+/// `<code>`.
+fn is_math_class(class: &str) -> bool {
+    matches!(class, "math math-inline" | "math math-display")
+}
+
+fn is_url_attribute(name: &str) -> bool {
+    URL_ATTRIBUTES.contains(&name) || SRCSET_ATTRIBUTES.contains(&name) || name == "data"
+}
+
+/// `true` for relative URLs and `http:`, `https:` and `mailto:` ones.
+///
+/// Browsers decode entities and drop whitespace before reading the scheme,
+/// so whitespace is ignored here and an entity before the first `/`, `?` or
+/// `#` counts as unsafe (it could hide a `:`).
+fn has_safe_scheme(url: &str) -> bool {
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    let head = &url[..url.find(['/', '?', '#']).unwrap_or(url.len())];
+    if head.contains(':') {
+        ["http:", "https:", "mailto:"]
+            .iter()
+            .any(|scheme| head.starts_with(scheme))
+    } else {
+        !head.contains('&')
+    }
+}
+
+/// Rewrite relative URLs in HTML to absolute ones.
+///
+/// Every URL-bearing attribute is rewritten, whatever its quoting: `href`,
+/// `src`, `poster`, `data-src`, `<object data>`, each candidate of `srcset`
+/// and `url(...)` in inline `style`. URLs are resolved against `page_url`
+/// (the chapter's own page) as a browser would, or against the site root
+/// `base_url` when there is no page. Links to `.md` files point at the
+/// rendered `.html` pages. Empty URLs, URLs with a scheme and fragment-only
+/// URLs without a `page_url` are kept as they are.
+#[must_use]
+pub fn make_urls_absolute(html: &str, base_url: &str, page_url: Option<&str>) -> String {
+    let root = format!("{}/", base_url.trim_end_matches('/'));
+    let base = page_url.unwrap_or(&root);
+    let resolve = |url: &str| -> String {
+        let url = url.trim();
+        if url.is_empty() || url.starts_with('#') && page_url.is_none() {
+            url.to_string()
+        } else {
+            resolve_url(base, url)
+        }
+    };
+
+    let settings = RewriteStrSettings {
+        element_content_handlers: vec![element!("*", |el| {
+            rewrite_element_urls(el, &resolve)?;
+            Ok(())
+        })],
+        ..RewriteStrSettings::new()
+    };
+    // The rewriter only fails on handler errors and memory limits; neither
+    // leaves usable output, so keep the HTML as it was.
+    rewrite_str(html, settings).unwrap_or_else(|_| html.to_string())
+}
+
+/// Every URL [`make_urls_absolute`] would resolve in `html`, in document
+/// order, so checks look at exactly the attributes the rewriter touches.
+pub(crate) fn element_urls(html: &str) -> Vec<String> {
+    let urls = RefCell::new(Vec::new());
+    let record = |url: &str| -> String {
+        urls.borrow_mut().push(url.trim().to_string());
+        url.to_string()
+    };
+    let settings = RewriteStrSettings {
+        element_content_handlers: vec![element!("*", |el| {
+            rewrite_element_urls(el, &record)?;
+            Ok(())
+        })],
+        ..RewriteStrSettings::new()
+    };
+    // Only the recorded URLs matter; a failed rewrite just records fewer.
+    let _ = rewrite_str(html, settings);
+    urls.into_inner()
+}
+
+fn rewrite_element_urls(
+    el: &mut Element<'_, '_>,
+    resolve: &impl Fn(&str) -> String,
+) -> Result<(), lol_html::errors::AttributeNameError> {
+    let is_object = el.tag_name() == "object";
+    let rewrites: Vec<(String, String)> = el
+        .attributes()
+        .iter()
+        .filter_map(|attr| {
+            let name = attr.name();
+            let value = attr.value();
+            let rewritten =
+                if URL_ATTRIBUTES.contains(&name.as_str()) || is_object && name == "data" {
+                    resolve(&value)
+                } else if SRCSET_ATTRIBUTES.contains(&name.as_str()) {
+                    rewrite_srcset(&value, resolve)
+                } else if name == "style" {
+                    rewrite_css_urls(&value, resolve)
+                } else {
+                    return None;
+                };
+            (rewritten != value).then_some((name, rewritten))
+        })
+        .collect();
+    for (name, value) in rewrites {
+        el.set_attribute(&name, &value)?;
+    }
+    Ok(())
+}
+
+/// Resolve each candidate URL of a `srcset`, keeping its width or density
+/// descriptor.
+fn rewrite_srcset(srcset: &str, resolve: &impl Fn(&str) -> String) -> String {
+    let mut candidates = Vec::new();
+    let mut rest = srcset;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+        if rest.is_empty() {
+            break;
+        }
+        let end = rest
+            .find(|c: char| c.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+        let (url, after) = rest.split_at(end);
+        rest = after;
+        // A URL ending in a comma has no descriptor; otherwise the
+        // descriptor runs to the next comma.
+        let (url, descriptor) = if url.ends_with(',') {
+            (url.trim_end_matches(','), "")
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            let (descriptor, after) = rest.split_at(end);
+            rest = after;
+            (url, descriptor.trim())
+        };
+        let url = resolve(url);
+        candidates.push(if descriptor.is_empty() {
+            url
+        } else {
+            format!("{url} {descriptor}")
+        });
+    }
+    candidates.join(", ")
+}
+
+/// Resolve every `url(...)` in a CSS declaration list, keeping its quotes.
+fn rewrite_css_urls(css: &str, resolve: &impl Fn(&str) -> String) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.to_ascii_lowercase().find("url(") {
+        let (before, after) = rest.split_at(start + 4);
+        out.push_str(before);
+        let inner = after.trim_start();
+        let quote = inner.chars().next().filter(|c| matches!(c, '"' | '\''));
+        let body = quote.map_or(inner, |q| &inner[q.len_utf8()..]);
+        let end = match quote {
+            Some(q) => body.find(q),
+            None => body.find(')'),
+        };
+        let Some(end) = end else {
+            rest = after;
+            break;
+        };
+        if let Some(q) = quote {
+            out.push(q);
+        }
+        out.push_str(&resolve(&body[..end]));
+        if let Some(q) = quote {
+            out.push(q);
+        }
+        rest = &body[end + quote.map_or(0, char::len_utf8)..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: Option<&str> = Some("https://example.com/posts/2024/hello.html");
+
+    #[test]
+    fn make_urls_absolute_rewrites_relative_src() {
+        let html = r#"<img src="images/foo.png"><img src="https://example.com/bar.png">"#;
+        let out = make_urls_absolute(html, "https://example.com/", None);
+        assert!(out.contains(r#"src="https://example.com/images/foo.png""#));
+        assert!(out.contains(r#"src="https://example.com/bar.png""#));
+    }
+
+    #[test]
+    fn make_urls_absolute_rewrites_relative_href() {
+        let html = r#"<a href="chapter/page.html">link</a>"#;
+        let out = make_urls_absolute(html, "https://example.com", None);
+        assert!(out.contains(r#"href="https://example.com/chapter/page.html""#));
+    }
+
+    #[test]
+    fn make_urls_absolute_resolves_against_the_page() {
+        let html = r##"<img src="img/a.png"><a href="../other.md#x">o</a><a href="#top">t</a>"##;
+        let out = make_urls_absolute(html, "https://example.com", PAGE);
+        assert_eq!(
+            out,
+            concat!(
+                r#"<img src="https://example.com/posts/2024/img/a.png">"#,
+                r#"<a href="https://example.com/posts/other.html#x">o</a>"#,
+                r#"<a href="https://example.com/posts/2024/hello.html#top">t</a>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn make_urls_absolute_handles_any_quoting() {
+        let html = "<img src='a.png' alt=x><img src=b.png><a href=c.md>c</a>";
+        let out = make_urls_absolute(html, "https://example.com", PAGE);
+        assert_eq!(
+            out,
+            concat!(
+                r#"<img src="https://example.com/posts/2024/a.png" alt=x>"#,
+                r#"<img src="https://example.com/posts/2024/b.png">"#,
+                r#"<a href="https://example.com/posts/2024/c.html">c</a>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn make_urls_absolute_rewrites_every_url_attribute() {
+        let html = concat!(
+            r#"<img srcset="s.png 1x, l.png 2x" data-src="lazy.png">"#,
+            r#"<video poster="p.jpg"></video><object data="d.svg"></object>"#,
+            r#"<div style="background: url('bg.png'), url(x.png)"></div>"#,
+        );
+        let out = make_urls_absolute(html, "https://example.com", None);
+        assert_eq!(
+            out,
+            concat!(
+                r#"<img srcset="https://example.com/s.png 1x, https://example.com/l.png 2x" "#,
+                r#"data-src="https://example.com/lazy.png">"#,
+                r#"<video poster="https://example.com/p.jpg"></video>"#,
+                r#"<object data="https://example.com/d.svg"></object>"#,
+                r#"<div style="background: url('https://example.com/bg.png'), "#,
+                r#"url(https://example.com/x.png)"></div>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn make_urls_absolute_ignores_lookalikes() {
+        let html = concat!(
+            r#"<div data-href="keep" data="keep"></div>"#,
+            "<p>write href=\"x\" and src=\"y\"</p>",
+            "<code>&lt;img src=\"z.png\"&gt;</code>",
+        );
+        assert_eq!(make_urls_absolute(html, "https://example.com", None), html);
+    }
+
+    #[test]
+    fn sanitize_html_drops_scripts_and_handlers() {
+        let html = concat!(
+            r#"<p onclick="steal()">Hi <script>alert(1)</script><b>there</b></p>"#,
+            r#"<iframe src="https://evil.example"></iframe><!-- note -->"#,
+            r#"<style>p { color: red }</style><custom-tag>kept text</custom-tag>"#,
+        );
+        assert_eq!(
+            sanitize_html(html, &Allowlist::default()),
+            "<p>Hi <b>there</b></p>kept text"
+        );
+    }
+
+    #[test]
+    fn sanitize_html_drops_unsafe_urls() {
+        let html = concat!(
+            r#"<a href="javascript:alert(1)">a</a><a href=" JaVa&#x09;script:x">b</a>"#,
+            r#"<a href="https://example.com/?a=1&amp;b=2">c</a><a href="../x.md">d</a>"#,
+            r#"<img src="data:text/html;base64,AAAA" alt="e">"#,
+        );
+        assert_eq!(
+            sanitize_html(html, &Allowlist::default()),
+            concat!(
+                "<a>a</a><a>b</a>",
+                r#"<a href="https://example.com/?a=1&amp;b=2">c</a><a href="../x.md">d</a>"#,
+                r#"<img alt="e">"#,
+            )
+        );
+    }
+
+    #[test]
+    fn sanitize_html_keeps_only_plain_styles() {
+        let html = concat!(
+            r#"<pre style="background-color:#fff;"><span style="color:#a71d5d;font-weight:bold;">fn</span></pre>"#,
+            r#"<p style="position: fixed; top: 0">x</p><p style="color: url(x)">y</p>"#,
+        );
+        assert_eq!(
+            sanitize_html(html, &Allowlist::default()),
+            "<pre><span>fn</span></pre><p>x</p><p>y</p>"
+        );
+        assert_eq!(
+            sanitize_html(html, &Allowlist::default().with_highlight_styles()),
+            concat!(
+                r#"<pre style="background-color:#fff;"><span style="color:#a71d5d;font-weight:bold;">fn</span></pre>"#,
+                "<p>x</p><p>y</p>",
+            )
+        );
+    }
+
+    #[test]
+    fn sanitize_html_follows_a_custom_allowlist() {
+        let mut allowlist = Allowlist::default();
+        allowlist.tags.insert("iframe".to_string());
+        allowlist.tags.remove("img");
+        allowlist.attributes.insert("onload".to_string());
+        let html =
+            r#"<iframe src="https://video.example/1" onload="x()"></iframe><img src="a.png">"#;
+        assert_eq!(
+            sanitize_html(html, &allowlist),
+            r#"<iframe src="https://video.example/1"></iframe>"#
+        );
+    }
+
+    #[test]
+    fn sanitize_html_keeps_mathml_only_when_allowed() {
+        let html = r#"<p><math display="inline"><mi mathvariant="normal">x</mi></math></p>"#;
+        assert_eq!(sanitize_html(html, &Allowlist::default()), "<p></p>");
+        assert_eq!(
+            sanitize_html(html, &Allowlist::default().with_mathml()),
+            html
+        );
+    }
+
+    #[test]
+    fn rewrite_srcset_keeps_descriptors() {
+        let resolve = |url: &str| format!("/{url}");
+        assert_eq!(
+            rewrite_srcset(" a.png 480w,b.png,  c.png 2x ", &resolve),
+            "/a.png 480w, /b.png, /c.png 2x"
+        );
+    }
+}
@@ -1,12 +1,21 @@
 //! JSON Feed 1.1 support.
 //!
-//! Enabled by the `json-feed` cargo feature. Converts an RSS [`Channel`]
-//! into a minimal JSON Feed 1.1 document.
+//! Enabled by the `json-feed` cargo feature. Renders a [`Feed`] page as a
+//! minimal JSON Feed 1.1 document; [`rss_to_json_feed`] does the same
+//! starting from an RSS [`Channel`].
 
-use chrono::DateTime;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use lol_html::{RewriteStrSettings, doc_text, element, end_tag, rewrite_str};
+use quick_xml::escape::resolve_html5_entity;
 use rss::Channel;
 use serde::Serialize;
-use serde_json::Value as JsonValue;
+
+use crate::error::{FeedError, Result};
+use crate::format::{FeedFormat, PageLinks, numbered_file};
+use crate::model::Feed;
 
 /// Minimal JSON Feed 1.1 document.
 #[derive(Serialize)]
@@ -33,51 +42,255 @@ pub struct JsonFeedItem {
     pub title: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content_html: Option<String>,
+    /// Plain-text excerpt, set when `content_html` holds the full chapter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub date_published: Option<String>,
-    /// Allows a simple string or a richer author object later.
+    /// Frontmatter `updated` (or the last commit with `date-source = "git"`).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub author: Option<JsonValue>,
+    pub date_modified: Option<String>,
+    /// Frontmatter `categories` and `tags`, carried over from RSS `<category>`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// JSON Feed 1.1 `authors`; the deprecated 1.0 `author` is not written.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<JsonFeedAuthor>,
 }
 
-/// Stable per-item id: prefer guid, then link, then title.
-fn item_id(item: &rss::Item) -> String {
-    item.guid()
-        .map(|g| g.value().to_string())
-        .or_else(|| item.link().map(str::to_string))
-        .unwrap_or_else(|| item.title().unwrap_or_default().to_string())
+/// A JSON Feed 1.1 author object. Emails have no place in JSON Feed.
+#[derive(Serialize)]
+pub struct JsonFeedAuthor {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<String>,
 }
 
 /// Convert an RSS 2.0 channel into a JSON Feed 1.1 structure.
+///
+/// Kept for compatibility: this goes through [`Feed::from_channel`], so only
+/// what RSS can carry survives. Prefer [`feed_to_json_feed`] with
+/// [`crate::FeedPage::feed`].
 #[must_use]
 pub fn rss_to_json_feed(
     channel: &Channel,
     feed_url: Option<&str>,
     next_url: Option<&str>,
 ) -> JsonFeed {
-    let items: Vec<JsonFeedItem> = channel
-        .items()
+    feed_to_json_feed(&Feed::from_channel(channel), feed_url, next_url)
+}
+
+/// Render a [`Feed`] page as a JSON Feed 1.1 structure.
+#[must_use]
+pub fn feed_to_json_feed(feed: &Feed, feed_url: Option<&str>, next_url: Option<&str>) -> JsonFeed {
+    let items: Vec<JsonFeedItem> = feed
+        .entries
         .iter()
-        .map(|item| JsonFeedItem {
-            id: item_id(item),
-            url: item.link().map(str::to_string),
-            title: item.title().map(str::to_string),
-            content_html: item.description().map(str::to_string),
-            date_published: item
-                .pub_date()
-                .and_then(|d| DateTime::parse_from_rfc2822(d).ok())
-                .map(|dt| dt.to_rfc3339()),
-            author: item.author().map(|a| serde_json::json!({ "name": a })),
+        .map(|entry| JsonFeedItem {
+            id: entry.id.clone(),
+            url: entry.url.clone(),
+            title: Some(entry.title.clone()).filter(|t| !t.is_empty()),
+            content_html: Some(entry.content_html.clone()),
+            summary: entry.summary_html.as_deref().map(html_to_text),
+            date_published: entry.published.map(|dt| dt.to_rfc3339()),
+            date_modified: entry.updated.map(|dt| dt.to_rfc3339()),
+            tags: entry.categories.clone(),
+            authors: entry
+                .authors
+                .iter()
+                .map(|a| JsonFeedAuthor {
+                    name: a.name.clone(),
+                    url: a.url.clone(),
+                    avatar: a.avatar.clone(),
+                })
+                .collect(),
         })
         .collect();
 
     JsonFeed {
         version: "https://jsonfeed.org/version/1.1".to_string(),
-        title: channel.title().to_string(),
-        home_page_url: Some(channel.link().to_string()),
+        title: feed.title.clone(),
+        home_page_url: Some(feed.home_url.clone()),
         feed_url: feed_url.map(str::to_string),
-        description: Some(channel.description().to_string()),
+        description: Some(feed.description.clone()),
         next_url: next_url.map(str::to_string),
         items,
     }
 }
+
+/// Elements whose start and end separate words in a plain-text summary.
+const BLOCK_TAGS: &str =
+    "p, br, li, div, h1, h2, h3, h4, h5, h6, pre, blockquote, tr, td, th, dt, dd";
+
+/// Elements whose content is never shown as text.
+const HIDDEN_TAGS: &str = "script, style, template";
+
+/// Reduce an HTML fragment to plain text, as JSON Feed `summary` expects:
+/// tags and comments are dropped (block-level tags leave a space), as is
+/// the content of `<script>`, `<style>` and `<template>`. Character
+/// references are decoded and whitespace is collapsed.
+fn html_to_text(html: &str) -> String {
+    let text = Rc::new(RefCell::new(String::with_capacity(html.len())));
+    let hidden = Rc::new(Cell::new(0_usize));
+    let node = RefCell::new(String::new());
+    let settings = RewriteStrSettings {
+        element_content_handlers: vec![
+            element!(BLOCK_TAGS, |el| {
+                text.borrow_mut().push(' ');
+                if el.can_have_content() {
+                    let text = Rc::clone(&text);
+                    el.on_end_tag(end_tag!(move |_| {
+                        text.borrow_mut().push(' ');
+                        Ok(())
+                    }))?;
+                }
+                Ok(())
+            }),
+            element!(HIDDEN_TAGS, |el| {
+                if el.can_have_content() {
+                    hidden.set(hidden.get() + 1);
+                    let hidden = Rc::clone(&hidden);
+                    el.on_end_tag(end_tag!(move |_| {
+                        hidden.set(hidden.get() - 1);
+                        Ok(())
+                    }))?;
+                }
+                Ok(())
+            }),
+        ],
+        // Comments are separate tokens, so collecting text skips them.
+        document_content_handlers: vec![doc_text!(|chunk| {
+            let mut raw = node.borrow_mut();
+            if hidden.get() == 0 {
+                raw.push_str(chunk.as_str());
+            }
+            // A reference can straddle chunks, so decode whole text nodes.
+            if chunk.last_in_text_node() {
+                decode_references(&raw, &mut text.borrow_mut());
+                raw.clear();
+            }
+            Ok(())
+        })],
+        ..RewriteStrSettings::new()
+    };
+    // Only the collected text matters; a failed rewrite just collects less.
+    let _ = rewrite_str(html, settings);
+    let text = text.borrow();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Append `raw` to `out` with named and numeric character references
+/// decoded. A reference that doesn't resolve is kept as written.
+fn decode_references(raw: &str, out: &mut String) {
+    // Longer than any named reference or in-range numeric one.
+    const MAX_REFERENCE_LEN: usize = 32;
+    let mut rest = raw;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp + 1..];
+        let reference = rest
+            .bytes()
+            .take(MAX_REFERENCE_LEN)
+            .position(|b| b == b';')
+            .and_then(|end| Some((end, resolve_reference(&rest[..end])?)));
+        match reference {
+            Some((end, decoded)) => {
+                out.push_str(&decoded);
+                rest = &rest[end + 1..];
+            }
+            None => out.push('&'),
+        }
+    }
+    out.push_str(rest);
+}
+
+/// Resolve the `name` of a `&name;` reference: `#123` and `#x7b` are code
+/// points (an invalid one becomes U+FFFD), anything else an HTML5 entity.
+fn resolve_reference(name: &str) -> Option<Cow<'static, str>> {
+    let Some(number) = name.strip_prefix('#') else {
+        return resolve_html5_entity(name).map(Cow::Borrowed);
+    };
+    let (digits, radix) = match number.strip_prefix(['x', 'X']) {
+        Some(hex) => (hex, 16),
+        None => (number, 10),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    let decoded = u32::from_str_radix(digits, radix)
+        .ok()
+        .and_then(char::from_u32)
+        .filter(|&c| c != '\0')
+        .unwrap_or(char::REPLACEMENT_CHARACTER);
+    Some(Cow::Owned(decoded.to_string()))
+}
+
+/// JSON Feed 1.1: `feed.json`, `feed2.json`, ... (`<stem>.json`) linked by
+/// `next_url`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonFeedFormat;
+
+impl FeedFormat for JsonFeedFormat {
+    fn name(&self) -> &'static str {
+        "JSON"
+    }
+
+    fn file_name(&self, stem: Option<&str>, page_idx: usize) -> String {
+        numbered_file(stem.unwrap_or("feed"), "json", page_idx)
+    }
+
+    fn render(&self, feed: &Feed, links: &PageLinks) -> Result<String> {
+        let json = feed_to_json_feed(feed, Some(&links.self_url), links.next_url.as_deref());
+        serde_json::to_string_pretty(&json).map_err(|e| FeedError::Render {
+            format: self.name().to_string(),
+            message: e.to_string(),
+        })
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_to_text_keeps_words_apart_only_between_blocks() {
+        assert_eq!(
+            html_to_text("<p>One <em>two</em>s &amp; three</p>\n<p>Four<br/>five</p>"),
+            "One twos & three Four five"
+        );
+    }
+
+    #[test]
+    fn html_to_text_reads_tags_with_angle_brackets_in_attributes() {
+        assert_eq!(
+            html_to_text(r#"<p><a title="1 > 0" href="a.html">One</a> two</p>"#),
+            "One two"
+        );
+    }
+
+    #[test]
+    fn html_to_text_decodes_numeric_and_named_references() {
+        assert_eq!(
+            html_to_text("<p>&#169; 2024 &#x2014; A&#X42;C &hellip; &nbsp;&lt;b&gt;</p>"),
+            "\u{a9} 2024 \u{2014} ABC \u{2026} <b>"
+        );
+        assert_eq!(
+            html_to_text("<p>&#0; &#xD800; &#99999999999; &bogus; AT&T</p>"),
+            "\u{fffd} \u{fffd} \u{fffd} &bogus; AT&T"
+        );
+    }
+
+    #[test]
+    fn html_to_text_drops_scripts_styles_and_comments() {
+        assert_eq!(
+            html_to_text(
+                "<p>Shown<!-- hidden --></p><script>let a = 1 < 2;</script>\
+                 <style>p { color: red }</style><p>too</p>"
+            ),
+            "Shown too"
+        );
+    }
+}
@@ -1,29 +1,73 @@
 //! mdbook-rss-feed core library.
 //!
 //! This module scans an mdBook src directory for chapters, extracts frontmatter
-//! and content, and turns them into one or more RSS 2.0 channels suitable for
-//! static hosting.
+//! and content, and turns them into format-neutral [`Feed`] pages rendered as
+//! RSS 2.0 channels (and Atom / JSON Feed documents) suitable for static
+//! hosting.
 
 mod article;
+mod date_source;
+mod diagnostics;
+mod directives;
 mod error;
 mod feed;
+mod format;
 pub(crate) mod frontmatter;
+mod html;
+mod math;
+mod model;
 mod preview;
+mod section;
+mod url;
+mod validate;
 
 #[cfg(feature = "atom")]
 mod atom_feed;
+#[cfg(feature = "highlight")]
+mod highlight;
 #[cfg(feature = "json-feed")]
 mod json_feed;
+#[cfg(feature = "mathml")]
+mod mathml;
 
 // Re-exports
-pub use article::{Article, articles_from_book_json, collect_articles, parse_markdown_file};
+pub use article::{
+    Article, articles_from_book_json, collect_articles, parse_markdown_file,
+    strip_excerpt_separators,
+};
 #[cfg(feature = "atom")]
-pub use atom_feed::rss_to_atom;
+pub use atom_feed::{AtomFormat, feed_to_atom, rss_to_atom};
+pub use date_source::{DateSource, GitHistory};
+pub use diagnostics::{Diagnostic, DiagnosticKind, Diagnostics};
+pub use directives::expand_directives;
 pub use error::{FeedError, Result};
 pub use feed::{
     BuildResult, DefaultBehavior, FeedOptions, FeedPage, build_feed, build_feed_from_articles,
+    feed_to_rss, topic_slug,
+};
+pub use format::{FeedFormat, FormatRegistry, PageLinks, RenderedPage, RssFormat, numbered_file};
+#[allow(deprecated)]
+pub use frontmatter::parse_frontmatter;
+pub use frontmatter::{
+    FeedVisibility, FrontMatter, first_h1, parse_frontmatter_lenient, resolve_title,
+    try_parse_frontmatter,
+};
+#[cfg(feature = "highlight")]
+pub use highlight::{DEFAULT_HIGHLIGHT_THEME, highlight_themes};
+pub use html::{
+    Allowlist, DEFAULT_ALLOWED_ATTRIBUTES, DEFAULT_ALLOWED_TAGS, MATHML_ATTRIBUTES, MATHML_TAGS,
+    make_urls_absolute, sanitize_html,
 };
-pub use frontmatter::{FeedVisibility, FrontMatter, first_h1, parse_frontmatter, resolve_title};
 #[cfg(feature = "json-feed")]
-pub use json_feed::{JsonFeed, JsonFeedItem, rss_to_json_feed};
-pub use preview::make_urls_absolute;
+pub use json_feed::{
+    JsonFeed, JsonFeedAuthor, JsonFeedFormat, JsonFeedItem, feed_to_json_feed, rss_to_json_feed,
+};
+pub use math::MathMode;
+pub use model::{Feed, FeedEntry, Person};
+pub use preview::{DEFAULT_EXCERPT_SEPARATOR, PreviewOptions, PreviewOverrides, PreviewUnit};
+pub use section::{PathFilter, select_articles};
+pub use url::resolve_url;
+pub use validate::{
+    FeedKind, Issue, Report, Severity, detect_format, validate_atom, validate_feed,
+    validate_json_feed, validate_rss,
+};
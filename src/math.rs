@@ -0,0 +1,187 @@
+//! TeX math in feed content.
+//!
+//! mdBook leaves `\( \)`, `\[ \]` and `$$` math in the page for MathJax to
+//! typeset in the browser, and feed readers don't run it. With a
+//! [`MathMode`], previews and full content get MathML, which most readers
+//! render natively, or the TeX in a marked `<code>` element instead.
+
+use std::str::FromStr;
+
+use pulldown_cmark::{Event, Tag, TagEnd};
+
+/// What to do with TeX math (`math`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathMode {
+    /// MathML, with the TeX kept as an annotation. Needs the `mathml`
+    /// feature; without it, and for TeX that doesn't parse, math falls back
+    /// to [`MathMode::Code`].
+    Mathml,
+    /// The TeX, delimiters included, in `<code class="math math-inline">`
+    /// (or `math-display`).
+    Code,
+}
+
+impl FromStr for MathMode {
+    type Err = String;
+    /// Parse from the string value in `book.toml`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "mathml" => Ok(Self::Mathml),
+            "code" => Ok(Self::Code),
+            other => Err(format!(
+                "unknown math mode `{other}`, expected \"mathml\" or \"code\""
+            )),
+        }
+    }
+}
+
+/// Delimiters MathJax looks for in mdBook's text, and whether they mark
+/// display math. `$$` is parsed by `pulldown_cmark` itself.
+const DELIMITERS: &[(&str, &str, bool)] = &[("\\(", "\\)", false), ("\\[", "\\]", true)];
+
+/// `<code>` holding `tex` between its delimiters.
+fn code_html(tex: &str, display: bool) -> String {
+    let (class, open, close) = if display {
+        ("math-display", "\\[", "\\]")
+    } else {
+        ("math-inline", "\\(", "\\)")
+    };
+    let escaped = tex
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    format!("<code class=\"math {class}\">{open}{escaped}{close}</code>")
+}
+
+fn math_html(tex: &str, display: bool, mode: MathMode) -> Event<'static> {
+    #[cfg(feature = "mathml")]
+    let mathml = match mode {
+        MathMode::Mathml => crate::mathml::tex_to_mathml(tex.trim(), display),
+        MathMode::Code => None,
+    };
+    #[cfg(not(feature = "mathml"))]
+    let mathml: Option<String> = {
+        let _ = mode;
+        None
+    };
+    Event::InlineHtml(mathml.unwrap_or_else(|| code_html(tex, display)).into())
+}
+
+/// Split `text` at `\(...\)` and `\[...\]`, rendering the math in between.
+fn split_math(text: &str, mode: MathMode, out: &mut Vec<Event<'_>>) {
+    let mut rest = text;
+    loop {
+        let next = DELIMITERS
+            .iter()
+            .filter_map(|&(open, close, display)| {
+                let start = rest.find(open)?;
+                let body = &rest[start + open.len()..];
+                let len = body.find(close)?;
+                Some((start, &body[..len], display))
+            })
+            .min_by_key(|&(start, ..)| start);
+        let Some((start, tex, display)) = next else {
+            break;
+        };
+        if start > 0 {
+            out.push(Event::Text(rest[..start].to_string().into()));
+        }
+        out.push(math_html(tex, display, mode));
+        rest = &rest[start + tex.len() + 4..];
+    }
+    if !rest.is_empty() {
+        out.push(Event::Text(rest.to_string().into()));
+    }
+}
+
+/// Render the math in `events` as `mode` says: `$...$` and `$$...$$`
+/// parsed by `pulldown_cmark`, and `\(...\)` and `\[...\]` in text outside
+/// code blocks. `pulldown_cmark` splits text at backslash escapes, so
+/// adjacent text is joined first; math across other inline elements isn't
+/// found, as MathJax wouldn't find it on the site either.
+pub(crate) fn render_math(events: Vec<Event<'_>>, mode: MathMode) -> Vec<Event<'_>> {
+    let mut out = Vec::with_capacity(events.len());
+    let mut text = String::new();
+    let mut in_code_block = false;
+    for event in events {
+        if let Event::Text(t) = &event
+            && !in_code_block
+        {
+            text.push_str(t);
+            continue;
+        }
+        if !text.is_empty() {
+            split_math(&text, mode, &mut out);
+            text.clear();
+        }
+        match event {
+            Event::InlineMath(tex) => out.push(math_html(&tex, false, mode)),
+            Event::DisplayMath(tex) => out.push(math_html(&tex, true, mode)),
+            Event::Start(Tag::CodeBlock(_)) => {
+                in_code_block = true;
+                out.push(event);
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                in_code_block = false;
+                out.push(event);
+            }
+            _ => out.push(event),
+        }
+    }
+    if !text.is_empty() {
+        split_math(&text, mode, &mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{Options, Parser, html};
+
+    fn render(md: &str, mode: MathMode) -> String {
+        let events = render_math(Parser::new_ext(md, Options::all()).collect(), mode);
+        let mut out = String::new();
+        html::push_html(&mut out, events.into_iter());
+        out
+    }
+
+    #[test]
+    fn math_mode_parses_book_toml_values() {
+        assert_eq!(" mathml".parse(), Ok(MathMode::Mathml));
+        assert_eq!("code".parse(), Ok(MathMode::Code));
+        assert!("katex".parse::<MathMode>().is_err());
+    }
+
+    #[test]
+    fn code_mode_marks_every_delimiter() {
+        let md = "Inline \\\\( a < b \\\\) and $x$.\n\n$$\n\\sum_i i\n$$\n\n\
+                  \\\\[ y \\\\] `\\\\( code \\\\)`\n\n```tex\n\\\\( block \\\\)\n```\n";
+        assert_eq!(
+            render(md, MathMode::Code),
+            "<p>Inline <code class=\"math math-inline\">\\( a &lt; b \\)</code> and \
+             <code class=\"math math-inline\">\\(x\\)</code>.</p>\n\
+             <p><code class=\"math math-display\">\\[\n\\sum_i i\n\\]</code></p>\n\
+             <p><code class=\"math math-display\">\\[ y \\]</code> \
+             <code>\\\\( code \\\\)</code></p>\n\
+             <pre><code class=\"language-tex\">\\\\( block \\\\)\n</code></pre>\n"
+        );
+    }
+
+    #[test]
+    fn unclosed_delimiters_stay_text() {
+        assert_eq!(
+            render("\\\\( a and \\\\[ b", MathMode::Code),
+            "<p>\\( a and \\[ b</p>\n"
+        );
+    }
+
+    #[cfg(not(feature = "mathml"))]
+    #[test]
+    fn mathml_falls_back_to_code_without_the_feature() {
+        assert_eq!(
+            render("$x$", MathMode::Mathml),
+            "<p><code class=\"math math-inline\">\\(x\\)</code></p>\n"
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! TeX to MathML conversion.
+//!
+//! Enabled by the `mathml` cargo feature, using `pulldown-latex`, which
+//! follows MathML Core as browsers implement it.
+
+use pulldown_latex::config::DisplayMode;
+use pulldown_latex::event::{Content, Event};
+use pulldown_latex::{Parser, RenderConfig, Storage, push_mathml};
+
+/// Private-use stand-ins for `<`, `>` and `&` in function names such as
+/// `\operatorname{c<d}`, which `pulldown-latex` writes as they are.
+const STAND_INS: [(char, char, &str); 3] = [
+    ('<', '\u{E000}', "&lt;"),
+    ('>', '\u{E001}', "&gt;"),
+    ('&', '\u{E002}', "&amp;"),
+];
+
+fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// `name` with `<`, `>` and `&` replaced by their [`STAND_INS`].
+fn stand_in_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            STAND_INS
+                .iter()
+                .find(|&&(original, ..)| original == c)
+                .map_or(c, |&(_, stand_in, _)| stand_in)
+        })
+        .collect()
+}
+
+/// `pulldown-latex` also writes the character of an identifier or operator
+/// as it is, as in `<mo><</mo>`. Its own markup never has a `<`, `>` or `&`
+/// between the end of a tag and the `</` of an end tag, so one there
+/// (negated or not) is that character.
+fn escape_char_content(mathml: &str) -> String {
+    let mut out = String::with_capacity(mathml.len());
+    let mut rest = mathml;
+    while let Some(idx) = rest.find('>') {
+        out.push_str(&rest[..=idx]);
+        rest = &rest[idx + 1..];
+        let mut chars = rest.chars();
+        if let Some(c @ ('<' | '>' | '&')) = chars.next() {
+            let after = chars.as_str();
+            if after
+                .strip_prefix('\u{338}')
+                .unwrap_or(after)
+                .starts_with("</")
+            {
+                out.push_str(&escape_text(&c.to_string()));
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    STAND_INS.iter().fold(out, |out, &(_, stand_in, escaped)| {
+        out.replace(stand_in, escaped)
+    })
+}
+
+/// `<math>` for `tex`, with the TeX as an `application/x-tex` annotation,
+/// or `None` when it doesn't parse.
+pub(crate) fn tex_to_mathml(tex: &str, display: bool) -> Option<String> {
+    // The stand-ins couldn't be told apart from the TeX's own.
+    if tex.contains(|c| STAND_INS.iter().any(|&(_, stand_in, _)| stand_in == c)) {
+        return None;
+    }
+    let storage = Storage::new();
+    let events: Vec<_> = Parser::new(tex, &storage).collect();
+    if events.iter().any(Result::is_err) {
+        return None;
+    }
+    let names: Vec<String> = events
+        .iter()
+        .filter_map(|event| match event {
+            Ok(Event::Content(Content::Function(name))) => Some(stand_in_name(name)),
+            _ => None,
+        })
+        .collect();
+    let mut names = names.iter();
+    let events = events.into_iter().map(|event| match event {
+        Ok(Event::Content(Content::Function(_))) => Ok(Event::Content(Content::Function(
+            names.next().map_or("", String::as_str),
+        ))),
+        other => other,
+    });
+
+    // The annotation is added here rather than by `pulldown-latex`, which
+    // would copy the TeX unescaped.
+    let config = RenderConfig {
+        display_mode: if display {
+            DisplayMode::Block
+        } else {
+            DisplayMode::Inline
+        },
+        annotation: None,
+        xml: true,
+        ..RenderConfig::default()
+    };
+    let mut mathml = String::new();
+    push_mathml(&mut mathml, events, config).ok()?;
+    let mathml = mathml.strip_suffix("</math>")?;
+    let (open, body) = mathml.split_at(mathml.find('>')? + 1);
+    Some(format!(
+        "{open}<semantics><mrow>{}</mrow>\
+         <annotation encoding=\"application/x-tex\">{}</annotation></semantics></math>",
+        escape_char_content(body),
+        escape_text(tex)
+    ))
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_tex_with_an_annotation() {
+        let mathml = tex_to_mathml("x^2", false).unwrap();
+        assert_eq!(
+            mathml,
+            "<math display=\"inline\" xmlns=\"http://www.w3.org/1998/Math/MathML\">\
+             <semantics><mrow><msup><mi>x</mi><mn>2</mn></msup></mrow>\
+             <annotation encoding=\"application/x-tex\">x^2</annotation></semantics></math>"
+        );
+        assert!(
+            tex_to_mathml("x", true)
+                .unwrap()
+                .starts_with("<math display=\"block\"")
+        );
+    }
+
+    #[test]
+    fn escapes_characters_copied_from_the_tex() {
+        let mathml = tex_to_mathml("a<b \\& c > d \\not< e", false).unwrap();
+        assert!(mathml.contains("<mo>&lt;</mo>"), "{mathml}");
+        assert!(mathml.contains("<mi>&amp;</mi>"), "{mathml}");
+        assert!(mathml.contains("<mo>&gt;</mo>"), "{mathml}");
+        assert!(mathml.contains("<mo>&lt;\u{338}</mo>"), "{mathml}");
+        assert!(
+            mathml.contains(">a&lt;b \\&amp; c &gt; d \\not&lt; e</annotation>"),
+            "{mathml}"
+        );
+
+        let mathml = tex_to_mathml("\\operatorname{c</mi>d} \\text{<mi>}", false).unwrap();
+        assert!(mathml.contains(">c&lt;/mi&gt;d</mi>"), "{mathml}");
+        assert!(mathml.contains("<mtext>&lt;mi&gt;</mtext>"), "{mathml}");
+        assert!(
+            mathml.contains(
+                "<annotation encoding=\"application/x-tex\">\
+                 \\operatorname{c&lt;/mi&gt;d} \\text{&lt;mi&gt;}</annotation>"
+            ),
+            "{mathml}"
+        );
+    }
+
+    #[test]
+    fn invalid_tex_is_none() {
+        assert_eq!(tex_to_mathml("\\frac{a", false), None);
+        assert_eq!(tex_to_mathml("\\nosuchcommand", false), None);
+        assert_eq!(tex_to_mathml("\u{E000}", false), None);
+    }
+}
@@ -0,0 +1,194 @@
+//! Format-neutral feed model.
+//!
+//! Articles are turned into a [`Feed`] of [`FeedEntry`]s once, and every
+//! output format (RSS 2.0, Atom 1.0, JSON Feed 1.1) is rendered from it. This
+//! keeps data RSS can't represent, such as an author's email separately from
+//! their name or the `updated` date, available to the formats that can.
+
+use chrono::{DateTime, Utc};
+use rss::Channel;
+use serde::Deserialize;
+
+/// A person credited on an entry.
+///
+/// Deserialized from a frontmatter `author` object or a
+/// `[preprocessor.rss-feed.authors.<id>]` table.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(default)]
+pub struct Person {
+    pub name: String,
+    /// Required by RSS `<author>`; optional everywhere else.
+    pub email: Option<String>,
+    /// Home page, emitted as Atom `<uri>` and JSON Feed `url`.
+    pub url: Option<String>,
+    /// Avatar image URL, JSON Feed only.
+    pub avatar: Option<String>,
+}
+
+impl Person {
+    /// A person known only by name.
+    #[must_use]
+    pub fn named(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// `true` when only `name` is set, as for a plain `author: Name`.
+    #[must_use]
+    pub fn is_name_only(&self) -> bool {
+        self.email.is_none() && self.url.is_none() && self.avatar.is_none()
+    }
+}
+
+/// One feed item, independent of the output format.
+#[derive(Debug, Clone)]
+pub struct FeedEntry {
+    /// Stable unique id: the item's permalink for generated feeds.
+    pub id: String,
+    pub title: String,
+    /// Absolute URL of the chapter page.
+    pub url: Option<String>,
+    /// Rendered HTML preview or full content, with absolute URLs.
+    pub content_html: String,
+    /// Short HTML excerpt, set when `content_html` holds the full chapter.
+    pub summary_html: Option<String>,
+    pub published: Option<DateTime<Utc>>,
+    pub updated: Option<DateTime<Utc>>,
+    pub authors: Vec<Person>,
+    /// Frontmatter `categories` then `tags`, without duplicates.
+    pub categories: Vec<String>,
+}
+
+/// One page of a feed, independent of the output format.
+///
+/// Page URLs (self/next/prev) depend on the format's file names, so they are
+/// passed to the renderers instead of being stored here.
+#[derive(Debug, Clone)]
+pub struct Feed {
+    pub title: String,
+    pub description: String,
+    /// The site's home page, e.g. `https://example.com/`.
+    pub home_url: String,
+    pub entries: Vec<FeedEntry>,
+}
+
+impl Feed {
+    /// Rebuild the model from an RSS channel, recovering what RSS carries.
+    ///
+    /// Used by the `rss_to_*` compatibility functions. `content:encoded`
+    /// becomes the content, with `<description>` as the summary. It is lossy:
+    /// an RSS `<author>` becomes the author's name verbatim (`dc:creator` names are
+    /// used when there is none), and only dates written by this crate's
+    /// `atom:updated` extension survive as `updated`.
+    #[must_use]
+    pub fn from_channel(channel: &Channel) -> Self {
+        Self {
+            title: channel.title().to_string(),
+            description: channel.description().to_string(),
+            home_url: channel.link().to_string(),
+            entries: channel.items().iter().map(entry_from_item).collect(),
+        }
+    }
+
+    /// Most recent `updated` (or `published`) date of any entry.
+    #[must_use]
+    pub fn last_updated(&self) -> Option<DateTime<Utc>> {
+        self.entries
+            .iter()
+            .filter_map(|e| e.updated.or(e.published))
+            .max()
+    }
+}
+
+/// Stable per-item id: prefer guid, then link, then title.
+fn item_id(item: &rss::Item) -> String {
+    item.guid()
+        .map(|g| g.value().to_string())
+        .or_else(|| item.link().map(str::to_string))
+        .unwrap_or_else(|| item.title().unwrap_or_default().to_string())
+}
+
+fn entry_from_item(item: &rss::Item) -> FeedEntry {
+    let rfc3339 = |value: &str| {
+        DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc))
+    };
+    FeedEntry {
+        id: item_id(item),
+        title: item.title().unwrap_or_default().to_string(),
+        url: item.link().map(str::to_string),
+        content_html: item
+            .content()
+            .or(item.description())
+            .unwrap_or_default()
+            .to_string(),
+        summary_html: item.content().and(item.description()).map(str::to_string),
+        published: item
+            .pub_date()
+            .and_then(|d| DateTime::parse_from_rfc2822(d).ok())
+            .map(|dt| dt.with_timezone(&Utc)),
+        updated: item
+            .extensions()
+            .get("atom")
+            .and_then(|m| m.get("updated"))
+            .and_then(|v| v.first())
+            .and_then(|e| e.value())
+            .and_then(rfc3339),
+        authors: match item.author() {
+            Some(author) => vec![Person::named(author)],
+            None => item
+                .dublin_core_ext()
+                .map(|dc| dc.creators().iter().map(Person::named).collect())
+                .unwrap_or_default(),
+        },
+        categories: item
+            .categories()
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect(),
+    }
+}
+
+#[allow(clippy::unwrap_used)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rss::{CategoryBuilder, GuidBuilder, ItemBuilder};
+
+    #[test]
+    fn from_channel_recovers_item_fields() {
+        let item = ItemBuilder::default()
+            .title(Some("Post".to_string()))
+            .link(Some("https://example.com/post.html".to_string()))
+            .guid(Some(GuidBuilder::default().value("urn:post").build()))
+            .description(Some("<p>Hi</p>".to_string()))
+            .pub_date(Some("Fri, 05 Jan 2024 00:00:00 +0000".to_string()))
+            .author(Some("me@example.com (Me)".to_string()))
+            .categories(vec![CategoryBuilder::default().name("rust").build()])
+            .build();
+        let channel = rss::ChannelBuilder::default()
+            .title("Book")
+            .link("https://example.com/")
+            .items(vec![item])
+            .build();
+
+        let feed = Feed::from_channel(&channel);
+        assert_eq!(feed.home_url, "https://example.com/");
+        let entry = &feed.entries[0];
+        assert_eq!(entry.id, "urn:post");
+        assert_eq!(entry.url.as_deref(), Some("https://example.com/post.html"));
+        assert_eq!(
+            entry.published.unwrap().to_rfc3339(),
+            "2024-01-05T00:00:00+00:00"
+        );
+        assert_eq!(entry.content_html, "<p>Hi</p>");
+        assert_eq!(entry.summary_html, None);
+        assert_eq!(entry.updated, None);
+        assert_eq!(entry.authors[0].name, "me@example.com (Me)");
+        assert_eq!(entry.categories, vec!["rust"]);
+        assert_eq!(feed.last_updated(), entry.published);
+    }
+}
@@ -4,24 +4,123 @@
 //! `description` override) into a short HTML preview suitable for an RSS
 //! `<description>`.
 
-use pulldown_cmark::{Options, Parser, html};
+use std::str::FromStr;
 
-/// Minimum body length (in chars) before we prefer it over the frontmatter
-/// `description` as the preview source.
+use pulldown_cmark::{CodeBlockKind, Event, LinkType, Options, Parser, Tag, TagEnd, html};
+use serde::Deserialize;
+
+use crate::html::{Allowlist, make_urls_absolute, sanitize_html};
+use crate::math::{MathMode, render_math};
+
+/// Default minimum body length (in chars) before we prefer it over the
+/// frontmatter `description` as the preview source.
 pub const MIN_BODY_PREVIEW_CHARS: usize = 80;
 
-/// Maximum amount of markdown (in chars) considered when building a preview,
-/// applied before HTML conversion to bound rendering cost on huge chapters.
-pub const PREVIEW_MD_SLICE_CHARS: usize = 4000;
+/// Default marker ending an explicit excerpt, as used by Jekyll and Hugo.
+pub const DEFAULT_EXCERPT_SEPARATOR: &str = "<!-- more -->";
+
+/// How a fenced code block marks the lines mdBook hides from readers.
+enum HiddenLines {
+    /// Rust: a line starting with `#` is hidden, unless `#!` or `#[` (an
+    /// attribute) follows; `##` stands for a literal `#`.
+    Rust,
+    /// `hidelines=<prefix>` in the info string, for any language.
+    Prefix(String),
+}
+
+impl HiddenLines {
+    /// The hidden-line convention of a code block, if it has one.
+    fn of(kind: &CodeBlockKind<'_>) -> Option<Self> {
+        let CodeBlockKind::Fenced(info) = kind else {
+            return None;
+        };
+        let mut words = info
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|w| !w.is_empty());
+        let language = words.next();
+        let prefix = language
+            .into_iter()
+            .chain(words)
+            .find_map(|w| w.strip_prefix("hidelines="))
+            .filter(|p| !p.is_empty());
+        match (prefix, language) {
+            (Some(prefix), _) => Some(Self::Prefix(prefix.to_string())),
+            (None, Some("rust")) => Some(Self::Rust),
+            (None, _) => None,
+        }
+    }
+
+    /// Drop the hidden lines of `code`, unescaping `##` in Rust.
+    fn strip(&self, code: &str) -> String {
+        let mut out = String::with_capacity(code.len());
+        for line in code.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+            match self {
+                Self::Prefix(prefix) if trimmed.starts_with(prefix.as_str()) => {}
+                Self::Rust if trimmed.starts_with('#') => match trimmed[1..].chars().next() {
+                    Some('#') => {
+                        out.push_str(&line[..line.len() - trimmed.len()]);
+                        out.push_str(&trimmed[1..]);
+                    }
+                    Some('!' | '[') => out.push_str(line),
+                    _ => {}
+                },
+                _ => out.push_str(line),
+            }
+        }
+        out
+    }
+}
+
+/// Parse Markdown into events, dropping the lines of code blocks that
+/// mdBook hides (see [`HiddenLines`]), so feeds show what the site shows.
+/// With a `math` mode, TeX math is rendered as it says.
+fn parse(md: &str, math: Option<MathMode>) -> Vec<Event<'_>> {
+    let mut events = Vec::new();
+    let mut code: Option<(HiddenLines, String)> = None;
+    for event in Parser::new_ext(md, Options::all()) {
+        match event {
+            Event::Start(Tag::CodeBlock(ref kind)) => {
+                code = HiddenLines::of(kind).map(|hidden| (hidden, String::new()));
+                events.push(event);
+            }
+            Event::Text(text) if code.is_some() => {
+                if let Some((_, buf)) = &mut code {
+                    buf.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((hidden, buf)) = code.take() {
+                    events.push(Event::Text(hidden.strip(&buf).into()));
+                }
+                events.push(event);
+            }
+            _ => events.push(event),
+        }
+    }
+    match math {
+        Some(mode) => render_math(events, mode),
+        None => events,
+    }
+}
 
 /// Render Markdown to HTML using `pulldown_cmark`.
 ///
 /// Used both for full-content feeds and for generating HTML previews from
-/// chapter bodies or frontmatter descriptions.
-pub fn markdown_to_html(md: &str) -> String {
+/// chapter bodies or frontmatter descriptions. Hidden lines of code blocks
+/// are left out, as on the rendered site. With a
+/// [`PreviewOptions::highlight`] theme (and the `highlight` feature), fenced
+/// code blocks get inline-styled highlighting, and with a
+/// [`PreviewOptions::math`] mode, TeX math becomes MathML or marked-up code.
+pub fn markdown_to_html(md: &str, opts: &PreviewOptions<'_>) -> String {
+    let events = parse(md, opts.math);
+    #[cfg(feature = "highlight")]
+    let events = match opts.highlight {
+        Some(theme) => crate::highlight::highlight_code_blocks(events, theme),
+        None => events,
+    };
     let mut html = String::new();
-    let parser = Parser::new_ext(md, Options::all());
-    html::push_html(&mut html, parser);
+    html::push_html(&mut html, events.into_iter());
     html
 }
 
@@ -67,6 +166,41 @@ pub fn strip_leading_boilerplate(md: &str) -> &str {
     }
 }
 
+/// Split `md` at the first line consisting of `separator` alone.
+///
+/// Returns the Markdown before and after that line, without the marker.
+/// Markers inside fenced code blocks don't count, so a chapter can document
+/// the separator itself.
+pub fn split_excerpt<'a>(md: &'a str, separator: &str) -> Option<(&'a str, &'a str)> {
+    let separator = separator.trim();
+    if separator.is_empty() {
+        return None;
+    }
+    let mut fence: Option<&str> = None;
+    let mut offset = 0;
+    for line in md.split_inclusive('\n') {
+        let trimmed = line.trim();
+        let start = offset;
+        offset += line.len();
+        if let Some(open) = fence {
+            if trimmed.starts_with(open) {
+                fence = None;
+            }
+        } else if let Some(open) = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f)) {
+            fence = Some(open);
+        } else if trimmed == separator {
+            return Some((&md[..start], &md[offset..]));
+        }
+    }
+    None
+}
+
+/// Remove the excerpt marker found by [`split_excerpt`], or `None` if the
+/// chapter has none.
+pub fn strip_excerpt_separator(md: &str, separator: &str) -> Option<String> {
+    split_excerpt(md, separator).map(|(before, after)| format!("{before}{after}"))
+}
+
 /// Take at most `max_chars` worth of UTF-8 text from `s`.
 pub fn utf8_prefix(s: &str, max_chars: usize) -> &str {
     if max_chars == 0 {
@@ -79,147 +213,484 @@ pub fn utf8_prefix(s: &str, max_chars: usize) -> &str {
     }
 }
 
-/// Return the first few `<p>` blocks from an HTML fragment.
-///
-/// Used to build the `<description>` preview for each item. At most
-/// `max_paragraphs` paragraphs are included, and the result is truncated to
-/// `max_chars` characters (UTF-8 safe). If no `<p>` is found, the original
-/// HTML is returned unchanged.
-pub fn html_first_paragraphs(html: &str, max_paragraphs: usize, max_chars: usize) -> String {
-    let mut out = String::new();
-    let mut start = 0;
-    let mut count = 0;
+/// What a preview's length limit counts (`preview-unit`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewUnit {
+    /// Characters of text, not counting markup.
+    #[default]
+    Chars,
+    /// Whitespace-separated words.
+    Words,
+}
 
-    while count < max_paragraphs {
-        let Some(rel) = html[start..].find("<p") else {
-            break;
-        };
-        let p_start = start + rel;
+impl PreviewUnit {
+    /// Length of `text` in this unit.
+    fn measure(self, text: &str) -> usize {
+        match self {
+            Self::Chars => text.chars().count(),
+            Self::Words => text.split_whitespace().count(),
+        }
+    }
+}
 
-        let Some(rel_close) = html[p_start..].find("</p>") else {
-            break;
-        };
-        let close = p_start + rel_close + "</p>".len();
+impl FromStr for PreviewUnit {
+    type Err = String;
+    /// Parse from the string value in `book.toml`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "chars" => Ok(Self::Chars),
+            "words" => Ok(Self::Words),
+            other => Err(format!(
+                "unknown preview-unit `{other}`, expected \"chars\" or \"words\""
+            )),
+        }
+    }
+}
 
-        out.push_str(&html[p_start..close]);
-        count += 1;
-        start = close;
+/// How previews are shortened and how the cut is marked.
+#[derive(Debug, Clone)]
+pub struct PreviewOptions<'a> {
+    /// Line that ends an explicit excerpt, e.g. `<!-- more -->`; `None`
+    /// always uses the paragraph heuristic.
+    pub excerpt_separator: Option<&'a str>,
+    /// Top-level paragraphs kept by the heuristic.
+    pub max_paragraphs: usize,
+    /// Length kept by the heuristic, counted in `unit`s of text.
+    pub max_length: usize,
+    pub unit: PreviewUnit,
+    /// Body length (in chars) from which the body is preferred over the
+    /// frontmatter `description`.
+    pub min_body_chars: usize,
+    /// Appended where text is cut mid-paragraph.
+    pub ellipsis: &'a str,
+    /// Text of a link to the chapter, appended to previews that leave
+    /// something out.
+    pub read_more: Option<&'a str>,
+    /// Theme to syntax-highlight fenced code blocks in, with inline styles.
+    /// Needs the `highlight` feature; ignored without it.
+    pub highlight: Option<&'a str>,
+    /// How TeX math is rendered; left as written when `None`. MathML needs
+    /// the `mathml` feature, and [`Allowlist::with_mathml`] when sanitizing.
+    pub math: Option<MathMode>,
+}
+
+impl Default for PreviewOptions<'_> {
+    fn default() -> Self {
+        Self {
+            excerpt_separator: Some(DEFAULT_EXCERPT_SEPARATOR),
+            max_paragraphs: 3,
+            max_length: 800,
+            unit: PreviewUnit::Chars,
+            min_body_chars: MIN_BODY_PREVIEW_CHARS,
+            ellipsis: "…",
+            read_more: None,
+            highlight: None,
+            math: None,
+        }
     }
+}
 
-    if out.is_empty() {
-        out = html.to_string();
+impl PreviewOptions<'_> {
+    /// These options with a chapter's frontmatter overrides applied.
+    #[must_use]
+    pub fn with_overrides(&self, overrides: &PreviewOverrides) -> Self {
+        Self {
+            max_paragraphs: overrides.paragraphs.unwrap_or(self.max_paragraphs),
+            max_length: overrides.max_chars.unwrap_or(self.max_length),
+            unit: overrides.unit.unwrap_or(self.unit),
+            min_body_chars: overrides.min_body_chars.unwrap_or(self.min_body_chars),
+            ..self.clone()
+        }
     }
+}
 
-    if out.chars().count() > max_chars {
-        out.chars().take(max_chars).collect()
-    } else {
-        out
+/// Per-chapter preview sizing from the `preview-*` frontmatter keys; unset
+/// fields keep the book-level [`PreviewOptions`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PreviewOverrides {
+    pub paragraphs: Option<usize>,
+    /// `preview-max-chars`, counted in `unit`s like
+    /// [`PreviewOptions::max_length`].
+    pub max_chars: Option<usize>,
+    pub min_body_chars: Option<usize>,
+    pub unit: Option<PreviewUnit>,
+}
+
+/// Cut `text` to at most `max` `unit`s, backing up to the last word
+/// boundary. A single word longer than the limit is only cut mid-word when
+/// it starts the preview (`at_start`), so the preview is never empty.
+fn cut_at_word(text: &str, max: usize, unit: PreviewUnit, at_start: bool) -> &str {
+    if unit == PreviewUnit::Words {
+        if max == 0 {
+            return "";
+        }
+        let mut words = 0;
+        let mut in_word = false;
+        for (idx, c) in text.char_indices() {
+            if !c.is_whitespace() {
+                in_word = true;
+            } else if in_word {
+                in_word = false;
+                words += 1;
+                if words == max {
+                    return &text[..idx];
+                }
+            }
+        }
+        return text.trim_end();
+    }
+    let prefix = utf8_prefix(text, max);
+    if text[prefix.len()..]
+        .chars()
+        .next()
+        .is_none_or(char::is_whitespace)
+    {
+        return prefix.trim_end();
+    }
+    match prefix.rfind(char::is_whitespace) {
+        Some(idx) => prefix[..idx].trim_end(),
+        None if at_start => prefix,
+        None => "",
     }
 }
 
-/// Rewrite relative URLs in HTML to absolute ones using `base_url`.
-///
-/// Rewrites `src="..."` and `href="..."` attributes. Skips URLs that are
-/// already absolute (`http://`, `https://`, `//`) or fragment-only (`#`).
-#[must_use]
-pub fn make_urls_absolute(html: &str, base_url: &str, page_url: Option<&str>) -> String {
-    let base = base_url.trim_end_matches('/');
-
-    let is_absolute = |url: &str| -> bool {
-        url.starts_with("http://")
-            || url.starts_with("https://")
-            || url.starts_with("//")
-            || url.is_empty()
-    };
+/// HTML elements without an end tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// An element left open at the point of a preview cut.
+enum OpenElement {
+    /// Opened by a `pulldown_cmark` start event.
+    Markdown(TagEnd),
+    /// Opened by a start tag in raw inline HTML, such as `<b>`.
+    Html(String),
+}
 
-    let mut result = String::with_capacity(html.len() + 64);
+/// Follow the start and end tags in a piece of raw inline HTML. An end tag
+/// closes the innermost element of that name opened by raw HTML; void
+/// elements, self-closing tags, comments and stray end tags change nothing.
+fn track_inline_html(html: &str, open: &mut Vec<OpenElement>) {
     let mut rest = html;
+    while let Some(idx) = rest.find('<') {
+        rest = &rest[idx + 1..];
+        let (closing, tag) = match rest.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, rest),
+        };
+        let name_len = tag
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+            .unwrap_or(tag.len());
+        let name = tag[..name_len].to_ascii_lowercase();
+        let Some(end) = rest.find('>') else {
+            break;
+        };
+        let self_closing = rest[..end].ends_with('/');
+        rest = &rest[end + 1..];
+        if name.is_empty() || !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            continue;
+        }
+        if closing {
+            if let Some(pos) = open
+                .iter()
+                .rposition(|el| matches!(el, OpenElement::Html(n) if *n == name))
+            {
+                open.remove(pos);
+            }
+        } else if !self_closing && !VOID_ELEMENTS.contains(&name.as_str()) {
+            open.push(OpenElement::Html(name));
+        }
+    }
+}
+
+/// Byte length of the tag at the start of `html`, up to and including its
+/// `>`, or `None` if it never ends. A `>` inside a quoted attribute value
+/// doesn't end the tag.
+fn tag_len(html: &str) -> Option<usize> {
+    let mut quote = None;
+    for (idx, c) in html.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(idx + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether `html` starts with markup rather than a literal `<`.
+fn starts_with_tag(html: &str) -> bool {
+    html.strip_prefix('<')
+        .and_then(|rest| rest.chars().next())
+        .is_some_and(|c| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?'))
+}
+
+/// How much of a raw HTML block fits in a preview.
+enum HtmlBlockCut {
+    /// All of it, using this many units of text.
+    Fits(usize),
+    /// The HTML up to a cut at a word boundary, and the end tags closing the
+    /// elements it leaves open.
+    Cut { kept: String, closing: String },
+}
 
+/// Fit the raw HTML block `html` into `budget` units of text.
+///
+/// Text between tags counts like Markdown text, except for whitespace-only
+/// runs between tags; tags and comments don't count. `unit` and `at_start`
+/// are passed on to [`cut_at_word`].
+fn cut_html_block(html: &str, budget: usize, unit: PreviewUnit, at_start: bool) -> HtmlBlockCut {
+    let mut open: Vec<OpenElement> = Vec::new();
+    let mut used = 0;
+    let mut rest = html;
     while !rest.is_empty() {
-        // Find the next src=" or href=" occurrence.
-        let next_src = rest.find("src=\"");
-        let next_href = rest.find("href=\"");
-
-        let (pos, prefix_len) = match (next_src, next_href) {
-            (None, None) => break,
-            (Some(s), None) => (s, 5),
-            (None, Some(h)) => (h, 6),
-            (Some(s), Some(h)) => {
-                if s <= h {
-                    (s, 5)
-                } else {
-                    (h, 6)
+        let markup_len = if rest.starts_with("<!--") {
+            rest.find("-->").map(|end| end + 3)
+        } else if starts_with_tag(rest) {
+            tag_len(rest)
+        } else {
+            let end = rest[1..]
+                .match_indices('<')
+                .map(|(idx, _)| idx + 1)
+                .find(|&idx| starts_with_tag(&rest[idx..]))
+                .unwrap_or(rest.len());
+            let text = &rest[..end];
+            let len = unit.measure(text);
+            if !text.trim().is_empty() && used + len > budget {
+                let mut cut = cut_at_word(text, budget - used, unit, at_start && used == 0);
+                // Don't leave half an entity behind.
+                if let Some(amp) = cut.rfind('&')
+                    && !cut[amp..].contains(';')
+                {
+                    cut = cut[..amp].trim_end();
                 }
+                let kept = format!("{}{cut}", &html[..html.len() - rest.len()]);
+                let closing = open
+                    .iter()
+                    .rev()
+                    .filter_map(|el| match el {
+                        OpenElement::Html(name) => Some(format!("</{name}>")),
+                        OpenElement::Markdown(_) => None,
+                    })
+                    .collect();
+                return HtmlBlockCut::Cut { kept, closing };
+            }
+            if !text.trim().is_empty() {
+                used += len;
             }
+            rest = &rest[end..];
+            continue;
         };
+        let Some(len) = markup_len else {
+            // An unterminated tag or comment is kept as the author wrote it.
+            break;
+        };
+        if !rest.starts_with("<!") {
+            track_inline_html(&rest[..len], &mut open);
+        }
+        rest = &rest[len..];
+    }
+    HtmlBlockCut::Fits(used)
+}
 
-        // Copy everything up to and including the attribute prefix.
-        result.push_str(&rest[..pos + prefix_len]);
-        rest = &rest[pos + prefix_len..];
+/// Events closing every element in `open`, innermost first.
+fn close_all(open: &mut Vec<OpenElement>) -> Vec<Event<'static>> {
+    open.drain(..)
+        .rev()
+        .map(|el| match el {
+            OpenElement::Markdown(end) => Event::End(end),
+            OpenElement::Html(name) => Event::InlineHtml(format!("</{name}>").into()),
+        })
+        .collect()
+}
 
-        // Find the closing quote.
-        if let Some(end) = rest.find('"') {
-            let url = &rest[..end];
+/// Render the first `opts.max_paragraphs` top-level paragraphs of `md`, cut
+/// to `opts.max_length` units of text.
+///
+/// Works on the `pulldown_cmark` event stream, so the cut never lands inside
+/// a tag, attribute or entity: text stops at a word boundary, the ellipsis
+/// is appended and every element still open is closed, including those
+/// opened by raw inline HTML such as `<b>`. Without any paragraph, all
+/// blocks are kept and only the length limit applies. The flag is `true`
+/// when anything was left out.
+pub fn first_paragraphs_html(md: &str, opts: &PreviewOptions<'_>) -> (String, bool) {
+    let events = parse(md, opts.math);
+
+    let mut blocks: Vec<&[Event<'_>]> = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (idx, event) in events.iter().enumerate() {
+        match event {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        if depth == 0 {
+            blocks.push(&events[start..=idx]);
+            start = idx + 1;
+        }
+    }
 
-            if is_absolute(url) {
-                // Already absolute, copy as-is
-                result.push_str(url);
-            } else if url.starts_with('#') {
-                if let Some(page) = page_url {
-                    result.push_str(page.trim_end_matches('/'));
+    let total = blocks.len();
+    let is_paragraph =
+        |block: &&[Event<'_>]| matches!(block.first(), Some(Event::Start(Tag::Paragraph)));
+    if blocks.iter().any(is_paragraph) {
+        blocks = blocks
+            .into_iter()
+            .filter(is_paragraph)
+            .take(opts.max_paragraphs)
+            .collect();
+    }
+    let mut truncated = blocks.len() < total;
+
+    let mut out: Vec<Event<'_>> = Vec::new();
+    let mut open: Vec<OpenElement> = Vec::new();
+    let mut budget = opts.max_length;
+    // pulldown_cmark splits an HTML block into one event per line.
+    let mut block_html = String::new();
+    for event in blocks.into_iter().flatten() {
+        match event {
+            Event::Start(tag) => open.push(OpenElement::Markdown(tag.to_end())),
+            Event::Html(html) => {
+                block_html.push_str(html);
+                continue;
+            }
+            Event::End(TagEnd::HtmlBlock) if !block_html.is_empty() => {
+                let html = std::mem::take(&mut block_html);
+                let at_start = budget == opts.max_length;
+                match cut_html_block(&html, budget, opts.unit, at_start) {
+                    HtmlBlockCut::Fits(len) => {
+                        budget -= len;
+                        out.push(Event::Html(html.into()));
+                    }
+                    HtmlBlockCut::Cut { kept, closing } => {
+                        out.push(Event::Html(kept.into()));
+                        if !opts.ellipsis.is_empty() {
+                            out.push(Event::Text(opts.ellipsis.to_string().into()));
+                        }
+                        out.push(Event::Html(closing.into()));
+                        out.extend(close_all(&mut open));
+                        truncated = true;
+                        break;
+                    }
+                }
+                open.pop();
+            }
+            Event::End(_) => {
+                // Raw HTML left open inside this element is closed with it.
+                while let Some(OpenElement::Html(_)) = open.pop() {}
+            }
+            Event::InlineHtml(html) => track_inline_html(html, &mut open),
+            Event::Text(text) | Event::Code(text) => {
+                let len = opts.unit.measure(text);
+                if len > budget {
+                    let at_start = budget == opts.max_length;
+                    let cut = cut_at_word(text, budget, opts.unit, at_start).to_string();
+                    if !cut.is_empty() {
+                        out.push(match event {
+                            Event::Code(_) => Event::Code(cut.into()),
+                            _ => Event::Text(cut.into()),
+                        });
+                    }
+                    if !opts.ellipsis.is_empty() {
+                        out.push(Event::Text(opts.ellipsis.to_string().into()));
+                    }
+                    out.extend(close_all(&mut open));
+                    truncated = true;
+                    break;
                 }
-                result.push_str(url);
-            } else {
-                let path = url.trim_start_matches('/');
-                result.push_str(base);
-                result.push('/');
-                result.push_str(path);
+                budget -= len;
             }
-            result.push('"');
-            rest = &rest[end + 1..];
+            _ => {}
         }
+        out.push(event.clone());
     }
 
-    result.push_str(rest);
-    result
+    let mut html = String::new();
+    html::push_html(&mut html, out.into_iter());
+    (html, truncated)
+}
+
+/// A `<p>` holding a link with `text` to `url`.
+fn read_more_html(text: &str, url: &str) -> String {
+    let link = Tag::Link {
+        link_type: LinkType::Inline,
+        dest_url: url.into(),
+        title: "".into(),
+        id: "".into(),
+    };
+    let mut html = String::new();
+    html::push_html(
+        &mut html,
+        [
+            Event::Start(Tag::Paragraph),
+            Event::Start(link),
+            Event::Text(text.into()),
+            Event::End(TagEnd::Link),
+            Event::End(TagEnd::Paragraph),
+        ]
+        .into_iter(),
+    );
+    html
 }
 
 /// Choose and render a preview source for an article body.
 ///
-/// When `full_preview` is `true`, the entire body is rendered to HTML.
-/// Otherwise a hybrid heuristic picks between the body and the frontmatter
-/// `description` (preferring the body once it's long enough), strips
-/// leading boilerplate, slices it down before HTML conversion, and finally
-/// keeps only the first few rendered paragraphs.
+/// When `full_preview` is `true`, the entire body is rendered to HTML, minus
+/// any excerpt marker. Otherwise everything before an excerpt separator line
+/// is the preview. Without one, a hybrid heuristic picks between the body
+/// and the frontmatter `description` (preferring the body once it's long
+/// enough), strips leading boilerplate and keeps the first few paragraphs
+/// (see [`first_paragraphs_html`]). The HTML is then reduced to
+/// `allowlist`, if given, before URLs are made absolute. Previews that leave
+/// something out end with the `read_more` link to `page_url`.
 pub fn render_preview(
     content: &str,
     description: Option<&str>,
     full_preview: bool,
+    opts: &PreviewOptions<'_>,
+    allowlist: Option<&Allowlist>,
     base_url: &str,
     page_url: Option<&str>,
 ) -> String {
-    let html = if full_preview {
-        markdown_to_html(content)
+    let excerpt = opts
+        .excerpt_separator
+        .and_then(|sep| split_excerpt(content, sep));
+    let (html, truncated) = if full_preview {
+        match excerpt {
+            Some((before, after)) => (markdown_to_html(&format!("{before}{after}"), opts), false),
+            None => (markdown_to_html(content, opts), false),
+        }
+    } else if let Some((before, after)) = excerpt {
+        (markdown_to_html(before, opts), !after.trim().is_empty())
     } else {
         let content_trimmed = content.trim();
         let body_len = content_trimmed.chars().count();
 
-        let source_md = if body_len >= MIN_BODY_PREVIEW_CHARS || description.is_none() {
+        let source_md = if body_len >= opts.min_body_chars || description.is_none() {
             content_trimmed
         } else {
             description.unwrap_or(content_trimmed)
         };
 
-        let source_md = strip_leading_boilerplate(source_md);
-        let source_md = utf8_prefix(source_md, PREVIEW_MD_SLICE_CHARS);
-
-        let raw_html = markdown_to_html(source_md);
-        html_first_paragraphs(&raw_html, 3, 800)
+        first_paragraphs_html(strip_leading_boilerplate(source_md), opts)
     };
 
-    make_urls_absolute(&html, base_url, page_url)
+    let html = match allowlist {
+        Some(allowlist) => sanitize_html(&html, allowlist),
+        None => html,
+    };
+    let mut html = make_urls_absolute(&html, base_url, page_url);
+    if truncated && let (Some(text), Some(url)) = (opts.read_more, page_url) {
+        html.push_str(&read_more_html(text, url));
+    }
+    html
 }
 
+#[allow(clippy::unwrap_used)]
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -243,40 +714,206 @@ mod tests {
         assert_eq!(prefix.chars().count(), 5);
     }
 
+    fn sized(max_paragraphs: usize, max_length: usize, ellipsis: &str) -> PreviewOptions<'_> {
+        PreviewOptions {
+            max_paragraphs,
+            max_length,
+            ellipsis,
+            ..PreviewOptions::default()
+        }
+    }
+
+    #[test]
+    fn first_paragraphs_html_extracts_up_to_limit() {
+        let md = "- a list\n\none\n\ntwo\n\nthree";
+        let (out, truncated) = first_paragraphs_html(md, &sized(2, 1000, "…"));
+        assert_eq!(out, "<p>one</p>\n<p>two</p>\n");
+        assert!(truncated);
+    }
+
+    #[test]
+    fn first_paragraphs_html_falls_back_without_paragraphs() {
+        let md = "<div>no paragraphs here</div>";
+        let (out, truncated) = first_paragraphs_html(md, &sized(2, 1000, "…"));
+        assert_eq!(out, md);
+        assert!(!truncated);
+    }
+
     #[test]
-    fn html_first_paragraphs_extracts_up_to_limit() {
-        let html = "<p>one</p><p>two</p><p>three</p>";
-        let out = html_first_paragraphs(html, 2, 1000);
-        assert_eq!(out, "<p>one</p><p>two</p>");
+    fn first_paragraphs_html_cuts_at_a_word_and_closes_elements() {
+        let md = "Read the [*linked* guide](guide.md) about `some_code` &amp; more.";
+        let (out, truncated) = first_paragraphs_html(md, &sized(3, 16, " […]"));
+        assert_eq!(
+            out,
+            "<p>Read the <a href=\"guide.md\"><em>linked</em> […]</a></p>\n"
+        );
+        assert!(truncated);
+
+        // The cut can land inside inline code or right after an entity.
+        let (out, _) = first_paragraphs_html(md, &sized(3, 40, "…"));
+        assert_eq!(
+            out,
+            "<p>Read the <a href=\"guide.md\"><em>linked</em> guide</a> about <code>some_code</code> &amp;…</p>\n"
+        );
+        // A long first word is cut rather than dropped.
+        let (out, _) = first_paragraphs_html("Supercalifragilistic", &sized(3, 5, "…"));
+        assert_eq!(out, "<p>Super…</p>\n");
+    }
+
+    #[test]
+    fn first_paragraphs_html_closes_raw_inline_html() {
+        let md = "Some <b>long bold <i>two words</i> here</b> and <br> more";
+        let (out, truncated) = first_paragraphs_html(md, &sized(3, 14, "…"));
+        assert_eq!(out, "<p>Some <b>long bold…</b></p>\n");
+        assert!(truncated);
+
+        let (out, _) = first_paragraphs_html(md, &sized(3, 18, "…"));
+        assert_eq!(out, "<p>Some <b>long bold <i>two…</i></b></p>\n");
+        // Closed tags, void elements and comments aren't closed again.
+        let (out, _) = first_paragraphs_html(md, &sized(3, 100, "…"));
+        assert_eq!(out, format!("<p>{md}</p>\n"));
+        let (out, _) = first_paragraphs_html("a <!-- <b> --> b c d", &sized(3, 4, "…"));
+        assert_eq!(out, "<p>a <!-- <b> --> b…</p>\n");
+    }
+
+    #[test]
+    fn first_paragraphs_html_cuts_html_blocks() {
+        let md = "<table>\n<tr><td>alpha beta</td><td>gamma</td></tr>\n<tr><td>delta</td></tr>\n</table>";
+        let (out, truncated) = first_paragraphs_html(md, &sized(3, 12, "…"));
+        assert_eq!(
+            out,
+            "<table>\n<tr><td>alpha beta</td><td>…</td></tr></table>"
+        );
+        assert!(truncated);
+
+        // Quoted `>` and comments are markup, not text.
+        let md = "<div title=\"a > b\"><!-- <p> -->one two three</div>";
+        let (out, _) = first_paragraphs_html(md, &sized(3, 7, "…"));
+        assert_eq!(out, "<div title=\"a > b\"><!-- <p> -->one two…</div>");
+        let (out, truncated) = first_paragraphs_html(md, &sized(3, 100, "…"));
+        assert_eq!(out, md);
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn first_paragraphs_html_counts_words() {
+        let opts = PreviewOptions {
+            unit: PreviewUnit::Words,
+            ..sized(3, 4, "…")
+        };
+        let (out, truncated) = first_paragraphs_html("One *two* three four five six.", &opts);
+        assert_eq!(out, "<p>One <em>two</em> three four…</p>\n");
+        assert!(truncated);
+        assert_eq!("words".parse::<PreviewUnit>(), Ok(PreviewUnit::Words));
+        assert!("lines".parse::<PreviewUnit>().is_err());
+    }
+
+    #[test]
+    fn preview_overrides_replace_only_what_they_set() {
+        let book = sized(3, 800, "…");
+        let chapter = book.with_overrides(&PreviewOverrides {
+            paragraphs: Some(1),
+            unit: Some(PreviewUnit::Words),
+            ..PreviewOverrides::default()
+        });
+        assert_eq!(chapter.max_paragraphs, 1);
+        assert_eq!(chapter.unit, PreviewUnit::Words);
+        assert_eq!(chapter.max_length, 800);
+        assert_eq!(chapter.min_body_chars, MIN_BODY_PREVIEW_CHARS);
+    }
+
+    #[test]
+    fn render_preview_appends_read_more_when_shortened() {
+        let opts = PreviewOptions {
+            max_paragraphs: 1,
+            read_more: Some("Read more"),
+            ..PreviewOptions::default()
+        };
+        let page = Some("https://example.com/post.html");
+        let body = "First paragraph of a long enough chapter to be used as the preview.\n\nSecond.";
+        let out = render_preview(body, None, false, &opts, None, "https://example.com", page);
+        assert!(
+            out.ends_with("<p><a href=\"https://example.com/post.html\">Read more</a></p>\n"),
+            "{out}"
+        );
+
+        let short = render_preview(
+            "Only this.",
+            None,
+            false,
+            &opts,
+            None,
+            "https://example.com",
+            page,
+        );
+        assert!(!short.contains("Read more"), "{short}");
     }
 
     #[test]
-    fn html_first_paragraphs_falls_back_without_p_tags() {
-        let html = "<div>no paragraphs here</div>";
-        let out = html_first_paragraphs(html, 2, 1000);
-        assert_eq!(out, html);
+    fn markdown_to_html_drops_hidden_rust_lines() {
+        let md = "```rust,editable\n# use std::fmt;\n#[derive(Debug)]\nstruct S;\n\
+                  # fn main() {\n    ## not hidden\n    #![allow(unused)]\n#\n# }\n```\n\n\
+                  ```python\n# a comment\n```\n\n\
+                  ```python hidelines=~\n~import os\nprint(1)\n```\n";
+        let html = markdown_to_html(md, &PreviewOptions::default());
+        assert!(
+            html.contains(concat!(
+                "#[derive(Debug)]\nstruct S;\n",
+                "    # not hidden\n    #![allow(unused)]\n</code>"
+            )),
+            "{html}"
+        );
+        assert!(!html.contains("use std::fmt") && !html.contains("fn main"));
+        assert!(html.contains("# a comment"), "{html}");
+        assert!(html.contains(">print(1)\n</code>") && !html.contains("import os"));
     }
 
     #[test]
     fn render_preview_full_uses_whole_body() {
         let body = "# Heading\n\nSome content.";
-        let out = render_preview(body, None, true, "https://example.com", None);
+        let opts = PreviewOptions::default();
+        let out = render_preview(body, None, true, &opts, None, "https://example.com", None);
         assert!(out.contains("Some content."));
         assert!(out.contains("<h1>"));
     }
 
     #[test]
-    fn make_urls_absolute_rewrites_relative_src() {
-        let html = r#"<img src="images/foo.png"><img src="https://example.com/bar.png">"#;
-        let out = make_urls_absolute(html, "https://example.com/", None);
-        assert!(out.contains(r#"src="https://example.com/images/foo.png""#));
-        assert!(out.contains(r#"src="https://example.com/bar.png""#));
+    fn split_excerpt_ignores_markers_in_code_blocks() {
+        let md = "Intro\n\n```md\n<!-- more -->\n```\n\nTeaser\n  <!-- more -->\nRest\n";
+        let (before, after) = split_excerpt(md, DEFAULT_EXCERPT_SEPARATOR).unwrap();
+        assert!(before.ends_with("Teaser\n"));
+        assert_eq!(after, "Rest\n");
+        assert_eq!(
+            split_excerpt("no marker here", DEFAULT_EXCERPT_SEPARATOR),
+            None
+        );
+        assert_eq!(split_excerpt("a\n\nb", ""), None);
     }
 
     #[test]
-    fn make_urls_absolute_rewrites_relative_href() {
-        let html = r#"<a href="chapter/page.html">link</a>"#;
-        let out = make_urls_absolute(html, "https://example.com", None);
-        assert!(out.contains(r#"href="https://example.com/chapter/page.html""#));
+    fn render_preview_stops_at_excerpt_separator() {
+        let body = "# Title\n\nShort [intro](intro.md).\n\n<!-- more -->\n\nThe rest.";
+        let opts = PreviewOptions::default();
+        let out = render_preview(
+            body,
+            Some("ignored"),
+            false,
+            &opts,
+            None,
+            "https://example.com",
+            None,
+        );
+        assert!(out.starts_with("<h1>Title</h1>"), "{out}");
+        assert!(
+            out.contains("href=\"https://example.com/intro.html\""),
+            "{out}"
+        );
+        assert!(!out.contains("The rest"));
+
+        let full = render_preview(body, None, true, &opts, None, "https://example.com", None);
+        assert!(
+            full.contains("The rest") && !full.contains("more"),
+            "{full}"
+        );
     }
 }
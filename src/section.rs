@@ -0,0 +1,156 @@
+//! Selecting chapters for section feeds.
+//!
+//! A section feed is a named subset of the book (e.g. everything under
+//! `releases/`) configured in `[preprocessor.rss-feed.feeds.<name>]`. Chapters
+//! are matched on [`Article::path`], the path relative to the `src/` root.
+
+use crate::article::Article;
+
+/// How a section feed picks its chapters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathFilter {
+    /// Match chapters under this directory (e.g. `"posts/"`, with or without
+    /// the trailing `/`), or the chapter at exactly this path. The prefix
+    /// only ends at a `/`, so `"posts"` doesn't match `postscript.md`.
+    Prefix(String),
+    /// Match chapters whose path matches a glob such as `"posts/**/*.md"`.
+    ///
+    /// `*` and `?` never cross a `/`; `**` matches any number of whole path
+    /// segments, including none.
+    Glob(String),
+}
+
+impl PathFilter {
+    /// Return `true` if `path` (relative to `src/`) belongs to this section.
+    ///
+    /// Backslashes in `path` are treated as `/` so Windows-style source paths
+    /// match the same patterns.
+    #[must_use]
+    pub fn matches(&self, path: &str) -> bool {
+        let path = path.replace('\\', "/");
+        match self {
+            Self::Prefix(prefix) => {
+                let prefix = prefix.trim_start_matches("./").trim_end_matches('/');
+                prefix.is_empty()
+                    || path == prefix
+                    || path
+                        .strip_prefix(prefix)
+                        .is_some_and(|rest| rest.starts_with('/'))
+            }
+            Self::Glob(pattern) => glob_match(pattern.trim_start_matches("./"), &path),
+        }
+    }
+}
+
+/// Clone the articles selected by `filter`, keeping their order.
+#[must_use]
+pub fn select_articles(articles: &[Article], filter: &PathFilter) -> Vec<Article> {
+    articles
+        .iter()
+        .filter(|a| filter.matches(&a.path))
+        .cloned()
+        .collect()
+}
+
+/// Match `path` against a glob `pattern` segment by segment.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    match_segments(&pattern, &path)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((seg, rest)) => match path.split_first() {
+            Some((name, path_rest)) => {
+                let seg: Vec<char> = seg.chars().collect();
+                let name: Vec<char> = name.chars().collect();
+                match_segment(&seg, &name) && match_segments(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Match one path segment against a pattern segment with `*` and `?`.
+fn match_segment(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| match_segment(rest, &name[skip..])),
+        Some(('?', rest)) => !name.is_empty() && match_segment(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && match_segment(rest, &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_matches_directory() {
+        let f = PathFilter::Prefix("releases/".to_string());
+        assert!(f.matches("releases/v1.md"));
+        assert!(f.matches("releases/2024/v2.md"));
+        assert!(!f.matches("posts/releases.md"));
+    }
+
+    #[test]
+    fn prefix_ends_at_a_segment_boundary() {
+        for prefix in ["posts", "posts/"] {
+            let f = PathFilter::Prefix(prefix.to_string());
+            assert!(f.matches("posts/a.md"));
+            assert!(f.matches("posts/2024/b.md"));
+            assert!(!f.matches("postscript.md"));
+            assert!(!f.matches("posts-archive/x.md"));
+        }
+        assert!(PathFilter::Prefix("posts/a.md".to_string()).matches("posts/a.md"));
+    }
+
+    #[test]
+    fn prefix_normalizes_backslashes() {
+        let f = PathFilter::Prefix("notes/".to_string());
+        assert!(f.matches(r"notes\today.md"));
+    }
+
+    #[test]
+    fn glob_single_star_stays_in_segment() {
+        let f = PathFilter::Glob("posts/*.md".to_string());
+        assert!(f.matches("posts/hello.md"));
+        assert!(!f.matches("posts/2024/hello.md"));
+        assert!(!f.matches("posts/hello.markdown"));
+    }
+
+    #[test]
+    fn glob_double_star_crosses_segments() {
+        let f = PathFilter::Glob("posts/**/*.md".to_string());
+        assert!(f.matches("posts/hello.md"));
+        assert!(f.matches("posts/2024/01/hello.md"));
+        assert!(!f.matches("notes/hello.md"));
+    }
+
+    #[test]
+    fn glob_question_mark_matches_one_char() {
+        let f = PathFilter::Glob("v?.md".to_string());
+        assert!(f.matches("v1.md"));
+        assert!(!f.matches("v10.md"));
+    }
+
+    #[test]
+    fn select_articles_keeps_order() {
+        use crate::frontmatter::parse_frontmatter_lenient;
+        let make = |path: &str| {
+            let (fm, content) = parse_frontmatter_lenient("Body.", path, None);
+            Article {
+                fm,
+                content,
+                path: path.to_string(),
+            }
+        };
+        let articles = vec![make("posts/b.md"), make("notes/x.md"), make("posts/a.md")];
+        let picked = select_articles(&articles, &PathFilter::Prefix("posts/".to_string()));
+        let paths: Vec<&str> = picked.iter().map(|a| a.path.as_str()).collect();
+        assert_eq!(paths, vec!["posts/b.md", "posts/a.md"]);
+    }
+}
@@ -0,0 +1,214 @@
+//! URL reference resolution (RFC 3986, section 5).
+//!
+//! Chapter HTML links to images and other chapters relative to the chapter's
+//! own page, so feeds resolve them the way a browser would on that page. Links
+//! to `.md` files become links to the rendered `.html` pages.
+
+/// The five components of a URI reference (RFC 3986, appendix B).
+struct Parts<'a> {
+    scheme: Option<&'a str>,
+    authority: Option<&'a str>,
+    path: &'a str,
+    query: Option<&'a str>,
+    fragment: Option<&'a str>,
+}
+
+/// `true` for a valid scheme: a letter followed by letters, digits, `+`, `-`
+/// or `.`.
+fn is_scheme(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+fn split(url: &str) -> Parts<'_> {
+    let (rest, fragment) = match url.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (url, None),
+    };
+    let (rest, query) = match rest.split_once('?') {
+        Some((rest, query)) => (rest, Some(query)),
+        None => (rest, None),
+    };
+    let (scheme, rest) = match rest.split_once(':') {
+        Some((scheme, rest)) if is_scheme(scheme) => (Some(scheme), rest),
+        _ => (None, rest),
+    };
+    let (authority, path) = match rest.strip_prefix("//") {
+        Some(rest) => {
+            let end = rest.find('/').unwrap_or(rest.len());
+            (Some(&rest[..end]), &rest[end..])
+        }
+        None => (None, rest),
+    };
+    Parts {
+        scheme,
+        authority,
+        path,
+        query,
+        fragment,
+    }
+}
+
+/// Resolve `.` and `..` segments (RFC 3986, section 5.2.4).
+fn remove_dot_segments(path: &str) -> String {
+    let absolute = path.starts_with('/');
+    let segments: Vec<&str> = path.split('/').skip(usize::from(absolute)).collect();
+    let last = segments.len().saturating_sub(1);
+    let mut out: Vec<&str> = Vec::with_capacity(segments.len());
+    for (idx, segment) in segments.into_iter().enumerate() {
+        match segment {
+            "." | ".." => {
+                if segment == ".." {
+                    out.pop();
+                }
+                // A trailing `.` or `..` still names a directory.
+                if idx == last {
+                    out.push("");
+                }
+            }
+            _ => out.push(segment),
+        }
+    }
+    let joined = out.join("/");
+    if absolute {
+        format!("/{joined}")
+    } else {
+        joined
+    }
+}
+
+/// Map a Markdown source path to the page mdBook renders it to:
+/// `intro.md` becomes `intro.html` and `dir/README.md` becomes
+/// `dir/index.html`. Other paths are returned unchanged.
+pub(crate) fn md_to_html_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let Some(stem) = path.strip_suffix(".md") else {
+        return path;
+    };
+    let html = format!("{stem}.html");
+    match html.strip_suffix("/README.html") {
+        Some(dir) => format!("{dir}/index.html"),
+        None => html,
+    }
+}
+
+/// Resolve `reference` against the absolute URL `base` (RFC 3986, section
+/// 5.2), as a browser would for a link on the page at `base`.
+///
+/// A relative reference to a `.md` file is rewritten to its `.html` page
+/// with [`md_to_html_path`]. References with their own scheme (`https:`,
+/// `mailto:`, `data:`, ...) are returned unchanged, and `//host/...` only
+/// gains the scheme of `base`.
+#[must_use]
+pub fn resolve_url(base: &str, reference: &str) -> String {
+    let r = split(reference);
+    if r.scheme.is_some() {
+        return reference.to_string();
+    }
+    let b = split(base);
+    let (authority, path, query) = if let Some(authority) = r.authority {
+        (Some(authority), remove_dot_segments(r.path), r.query)
+    } else if r.path.is_empty() {
+        (b.authority, b.path.to_string(), r.query.or(b.query))
+    } else {
+        let path = if r.path.starts_with('/') {
+            r.path.to_string()
+        } else if b.authority.is_some() && b.path.is_empty() {
+            format!("/{}", r.path)
+        } else {
+            let dir = b.path.rfind('/').map_or("", |idx| &b.path[..=idx]);
+            format!("{dir}{}", r.path)
+        };
+        (
+            b.authority,
+            md_to_html_path(&remove_dot_segments(&path)),
+            r.query,
+        )
+    };
+
+    let mut url = String::with_capacity(base.len() + reference.len());
+    if let Some(scheme) = b.scheme {
+        url.push_str(scheme);
+        url.push(':');
+    }
+    if let Some(authority) = authority {
+        url.push_str("//");
+        url.push_str(authority);
+    }
+    url.push_str(&path);
+    if let Some(query) = query {
+        url.push('?');
+        url.push_str(query);
+    }
+    if let Some(fragment) = r.fragment {
+        url.push('#');
+        url.push_str(fragment);
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_rfc_3986_examples() {
+        // RFC 3986, section 5.4.
+        let base = "http://a/b/c/d;p?q";
+        for (reference, expected) in [
+            ("g:h", "g:h"),
+            ("g", "http://a/b/c/g"),
+            ("./g", "http://a/b/c/g"),
+            ("g/", "http://a/b/c/g/"),
+            ("/g", "http://a/g"),
+            ("//g", "http://g"),
+            ("?y", "http://a/b/c/d;p?y"),
+            ("g?y", "http://a/b/c/g?y"),
+            ("#s", "http://a/b/c/d;p?q#s"),
+            ("g?y#s", "http://a/b/c/g?y#s"),
+            (";x", "http://a/b/c/;x"),
+            ("", "http://a/b/c/d;p?q"),
+            (".", "http://a/b/c/"),
+            ("..", "http://a/b/"),
+            ("../g", "http://a/b/g"),
+            ("../..", "http://a/"),
+            ("../../../g", "http://a/g"),
+            ("/./g", "http://a/g"),
+            ("g/../h", "http://a/b/c/h"),
+            ("g;x=1/./y", "http://a/b/c/g;x=1/y"),
+        ] {
+            assert_eq!(resolve_url(base, reference), expected, "{reference}");
+        }
+    }
+
+    #[test]
+    fn resolves_against_the_chapter_directory() {
+        let page = "https://site.example/posts/2024/hello.html";
+        assert_eq!(
+            resolve_url(page, "img/a.png"),
+            "https://site.example/posts/2024/img/a.png"
+        );
+        assert_eq!(
+            resolve_url(page, "../other.md#intro"),
+            "https://site.example/posts/other.html#intro"
+        );
+        assert_eq!(
+            resolve_url(page, "../README.md"),
+            "https://site.example/posts/index.html"
+        );
+        // Only relative references are rewritten.
+        assert_eq!(
+            resolve_url(page, "https://github.com/o/r/blob/main/README.md"),
+            "https://github.com/o/r/blob/main/README.md"
+        );
+    }
+
+    #[test]
+    fn md_to_html_path_matches_mdbook() {
+        assert_eq!(md_to_html_path("intro.md"), "intro.html");
+        assert_eq!(md_to_html_path("guide\\README.md"), "guide/index.html");
+        assert_eq!(md_to_html_path("README.md"), "README.html");
+        assert_eq!(md_to_html_path("img/a.png"), "img/a.png");
+    }
+}
@@ -0,0 +1,935 @@
+//! Offline feed validation.
+//!
+//! Checks RSS 2.0, Atom 1.0 and JSON Feed 1.1 documents for the spec
+//! violations the W3C and JSON Feed validators report most often: missing
+//! required elements, malformed dates, relative URLs, `<author>` values
+//! without an email address, duplicate GUIDs/ids and a self link that does
+//! not point at the feed itself. This is not a full validator, but it catches
+//! what readers actually trip over without a network round trip.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use chrono::DateTime;
+use quick_xml::encoding::Decoder;
+use quick_xml::escape::resolve_xml_entity;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, XmlVersion};
+use serde_json::{Map, Value};
+
+use crate::html::element_urls;
+
+const ATOM_NS: &str = "http://www.w3.org/2005/Atom";
+const JSON_FEED_1_1: &str = "https://jsonfeed.org/version/1.1";
+const JSON_FEED_1_0: &str = "https://jsonfeed.org/version/1";
+
+/// How serious a validation [`Issue`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Allowed by the spec, but likely to trip up some feed readers.
+    Warning,
+    /// A spec violation; validators reject the feed.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+/// Feed format recognised by [`detect_format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedKind {
+    Rss,
+    Atom,
+    JsonFeed,
+}
+
+impl fmt::Display for FeedKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Rss => "RSS 2.0",
+            Self::Atom => "Atom 1.0",
+            Self::JsonFeed => "JSON Feed",
+        })
+    }
+}
+
+/// One problem found in a feed document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub severity: Severity,
+    /// Where the problem is: `document`, `channel`, `feed`, `item 3`, `entry 1`.
+    pub location: String,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.location, self.message)
+    }
+}
+
+/// Every [`Issue`] found in one document, in document order.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    /// `true` if any issue is a [`Severity::Error`].
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == Severity::Error)
+    }
+
+    /// Number of issues with the given severity.
+    #[must_use]
+    pub fn count(&self, severity: Severity) -> usize {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == severity)
+            .count()
+    }
+
+    fn push(&mut self, severity: Severity, location: &str, message: String) {
+        self.issues.push(Issue {
+            severity,
+            location: location.to_string(),
+            message,
+        });
+    }
+
+    fn error(&mut self, location: &str, message: impl Into<String>) {
+        self.push(Severity::Error, location, message.into());
+    }
+
+    fn warning(&mut self, location: &str, message: impl Into<String>) {
+        self.push(Severity::Warning, location, message.into());
+    }
+}
+
+/// Minimal owned XML element: enough structure for the checks below.
+#[derive(Debug, Default)]
+struct Element {
+    /// Qualified name as written, e.g. `atom:link`.
+    name: String,
+    attrs: Vec<(String, String)>,
+    text: String,
+    children: Vec<Element>,
+}
+
+impl Element {
+    fn from_start(start: &BytesStart<'_>, decoder: Decoder) -> std::result::Result<Self, String> {
+        let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+        let mut attrs = Vec::new();
+        for attr in start.attributes() {
+            let attr = attr.map_err(|e| format!("malformed attribute on <{name}>: {e}"))?;
+            let value = attr
+                .decoded_and_normalized_value(XmlVersion::Implicit1_0, decoder)
+                .map_err(|e| format!("malformed attribute on <{name}>: {e}"))?;
+            attrs.push((
+                String::from_utf8_lossy(attr.key.as_ref()).into_owned(),
+                value.into_owned(),
+            ));
+        }
+        Ok(Self {
+            name,
+            attrs,
+            ..Self::default()
+        })
+    }
+
+    fn local_name(&self) -> &str {
+        self.name
+            .rsplit_once(':')
+            .map_or(self.name.as_str(), |(_, local)| local)
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|c| c.text.trim())
+    }
+}
+
+/// Append a finished element to its parent, or make it the document root.
+fn attach(stack: &mut [Element], root: &mut Option<Element>, element: Element) {
+    match stack.last_mut() {
+        Some(parent) => parent.children.push(element),
+        None => {
+            root.get_or_insert(element);
+        }
+    }
+}
+
+/// Parse `xml` into an [`Element`] tree rooted at the document element.
+fn parse_xml(xml: &str) -> std::result::Result<Element, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut stack: Vec<Element> = Vec::new();
+    let mut root: Option<Element> = None;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("not well-formed XML: {e}"))?;
+        match event {
+            Event::Start(start) => stack.push(Element::from_start(&start, reader.decoder())?),
+            Event::Empty(start) => {
+                let element = Element::from_start(&start, reader.decoder())?;
+                attach(&mut stack, &mut root, element);
+            }
+            Event::End(_) => {
+                if let Some(element) = stack.pop() {
+                    attach(&mut stack, &mut root, element);
+                }
+            }
+            Event::Text(text) => {
+                if let Some(top) = stack.last_mut() {
+                    let text = text.decode().map_err(|e| format!("bad text: {e}"))?;
+                    top.text.push_str(&text);
+                }
+            }
+            Event::CData(data) => {
+                if let Some(top) = stack.last_mut() {
+                    let data = data.decode().map_err(|e| format!("bad CDATA: {e}"))?;
+                    top.text.push_str(&data);
+                }
+            }
+            Event::GeneralRef(entity) => {
+                if let Some(top) = stack.last_mut() {
+                    let name = entity.decode().map_err(|e| format!("bad entity: {e}"))?;
+                    if let Some(resolved) = resolve_xml_entity(&name) {
+                        top.text.push_str(resolved);
+                    } else if let Some(ch) = entity
+                        .resolve_char_ref()
+                        .map_err(|e| format!("bad character reference: {e}"))?
+                    {
+                        top.text.push(ch);
+                    } else {
+                        return Err(format!("undefined entity `&{name};`"));
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if let Some(open) = stack.last() {
+        return Err(format!("unexpected end of document inside <{}>", open.name));
+    }
+    root.ok_or_else(|| "document has no root element".to_string())
+}
+
+/// `true` for URLs with a scheme (`https:`, `mailto:`, `tag:`, `urn:` ...).
+/// `http(s)` URLs additionally need a host.
+fn is_absolute_url(url: &str) -> bool {
+    let Some((scheme, rest)) = url.split_once(':') else {
+        return false;
+    };
+    let valid_scheme = scheme
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    if !valid_scheme {
+        return false;
+    }
+    if scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https") {
+        return rest.strip_prefix("//").is_some_and(|host| !host.is_empty());
+    }
+    !rest.is_empty()
+}
+
+/// `user@example.com`: something before the `@`, a dotted domain after it.
+fn is_email(value: &str) -> bool {
+    value.split_once('@').is_some_and(|(local, domain)| {
+        !local.is_empty()
+            && domain.contains('.')
+            && !domain.starts_with('.')
+            && !domain.ends_with('.')
+            && !value.chars().any(char::is_whitespace)
+    })
+}
+
+/// URLs in `html` that a feed reader cannot resolve on its own, from the
+/// same attributes [`make_urls_absolute`](crate::make_urls_absolute)
+/// rewrites. Fragment-only (`#top`) and protocol-relative (`//cdn`) URLs
+/// are fine.
+fn relative_urls(html: &str) -> Vec<String> {
+    element_urls(html)
+        .into_iter()
+        .filter(|url| {
+            !url.is_empty()
+                && !url.starts_with('#')
+                && !url.starts_with("//")
+                && !is_absolute_url(url)
+        })
+        .collect()
+}
+
+fn check_url(report: &mut Report, location: &str, what: &str, url: &str) {
+    if !is_absolute_url(url) {
+        report.error(location, format!("{what} `{url}` is not an absolute URL"));
+    }
+}
+
+fn check_rfc822(report: &mut Report, location: &str, what: &str, value: &str) {
+    if DateTime::parse_from_rfc2822(value).is_err() {
+        report.error(
+            location,
+            format!("{what} `{value}` is not a valid RFC 822 date"),
+        );
+    }
+}
+
+fn check_rfc3339(report: &mut Report, location: &str, what: &str, value: &str) {
+    if DateTime::parse_from_rfc3339(value).is_err() {
+        report.error(
+            location,
+            format!("{what} `{value}` is not a valid RFC 3339 date"),
+        );
+    }
+}
+
+fn check_content_urls(report: &mut Report, location: &str, what: &str, html: &str) {
+    for url in relative_urls(html) {
+        report.warning(
+            location,
+            format!("relative URL `{url}` in {what} will not resolve in feed readers"),
+        );
+    }
+}
+
+/// Check the feed's self link, and that it matches `expected` when known.
+fn check_self_link(
+    report: &mut Report,
+    location: &str,
+    what: &str,
+    found: Option<&str>,
+    expected: Option<&str>,
+) {
+    let Some(found) = found else {
+        report.warning(location, format!("missing {what}"));
+        return;
+    };
+    check_url(report, location, what, found);
+    if let Some(expected) = expected
+        && found != expected
+    {
+        report.error(
+            location,
+            format!("{what} `{found}` does not match the feed's URL `{expected}`"),
+        );
+    }
+}
+
+/// Guess the format of `content` from its first non-whitespace token.
+#[must_use]
+pub fn detect_format(content: &str) -> Option<FeedKind> {
+    let content = content.trim_start_matches('\u{feff}').trim_start();
+    if content.starts_with('{') {
+        return Some(FeedKind::JsonFeed);
+    }
+    let mut reader = Reader::from_str(content);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(start) | Event::Empty(start)) => {
+                return match start.local_name().as_ref() {
+                    b"rss" => Some(FeedKind::Rss),
+                    b"feed" => Some(FeedKind::Atom),
+                    _ => None,
+                };
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            Ok(_) => {}
+        }
+    }
+}
+
+/// Validate an RSS, Atom or JSON feed, detecting the format first.
+///
+/// `self_url` is the URL the document will be published at. When given, the
+/// feed's own self link (`atom:link rel="self"`, `<link rel="self">` or
+/// `feed_url`) must match it exactly.
+#[must_use]
+pub fn validate_feed(content: &str, self_url: Option<&str>) -> Report {
+    match detect_format(content) {
+        Some(FeedKind::Rss) => validate_rss(content, self_url),
+        Some(FeedKind::Atom) => validate_atom(content, self_url),
+        Some(FeedKind::JsonFeed) => validate_json_feed(content, self_url),
+        None => {
+            let mut report = Report::default();
+            report.error("document", "not an RSS 2.0, Atom 1.0 or JSON Feed document");
+            report
+        }
+    }
+}
+
+/// Validate an RSS 2.0 document. See [`validate_feed`] for `self_url`.
+#[must_use]
+pub fn validate_rss(xml: &str, self_url: Option<&str>) -> Report {
+    let mut report = Report::default();
+    let root = match parse_xml(xml) {
+        Ok(root) => root,
+        Err(msg) => {
+            report.error("document", msg);
+            return report;
+        }
+    };
+    if root.name != "rss" {
+        report.error(
+            "document",
+            format!("root element is <{}>, expected <rss>", root.name),
+        );
+        return report;
+    }
+    if root.attr("version") != Some("2.0") {
+        report.error("rss", "missing or unsupported version, expected \"2.0\"");
+    }
+    let Some(channel) = root.child("channel") else {
+        report.error("rss", "missing required <channel>");
+        return report;
+    };
+
+    for name in ["title", "link", "description"] {
+        if channel.child(name).is_none() {
+            report.error("channel", format!("missing required <{name}>"));
+        }
+    }
+    if let Some(link) = channel.child_text("link") {
+        check_url(&mut report, "channel", "<link>", link);
+    }
+    for name in ["pubDate", "lastBuildDate"] {
+        if let Some(date) = channel.child_text(name) {
+            check_rfc822(&mut report, "channel", &format!("<{name}>"), date);
+        }
+    }
+
+    // `atom:link` (any prefix) as opposed to the channel's own `<link>`.
+    let self_link = channel
+        .children
+        .iter()
+        .filter(|c| c.local_name() == "link" && c.name != "link")
+        .find(|c| c.attr("rel") == Some("self"))
+        .and_then(|c| c.attr("href"));
+    check_self_link(
+        &mut report,
+        "channel",
+        "atom:link rel=\"self\"",
+        self_link,
+        self_url,
+    );
+
+    let mut guids = HashSet::new();
+    for (idx, item) in channel.children("item").enumerate() {
+        let loc = format!("item {}", idx + 1);
+        if item.child("title").is_none() && item.child("description").is_none() {
+            report.error(&loc, "an item needs a <title> or a <description>");
+        }
+        if let Some(link) = item.child_text("link") {
+            check_url(&mut report, &loc, "<link>", link);
+        }
+        if let Some(guid) = item.child("guid") {
+            let value = guid.text.trim();
+            if guid.attr("isPermaLink") != Some("false") {
+                check_url(&mut report, &loc, "permalink <guid>", value);
+            }
+            if !guids.insert(value) {
+                report.error(&loc, format!("duplicate <guid> `{value}`"));
+            }
+        }
+        if let Some(date) = item.child_text("pubDate") {
+            check_rfc822(&mut report, &loc, "<pubDate>", date);
+        }
+        for name in ["atom:updated", "dcterms:modified"] {
+            if let Some(date) = item.child_text(name) {
+                check_rfc3339(&mut report, &loc, &format!("<{name}>"), date);
+            }
+        }
+        if let Some(author) = item.child_text("author")
+            && !author.split_whitespace().next().is_some_and(is_email)
+        {
+            report.error(
+                &loc,
+                format!(
+                    "<author> `{author}` must start with an email address, \
+                     e.g. `jane@example.com (Jane)`"
+                ),
+            );
+        }
+        for name in ["description", "content:encoded"] {
+            if let Some(html) = item.child_text(name) {
+                check_content_urls(&mut report, &loc, &format!("<{name}>"), html);
+            }
+        }
+    }
+    report
+}
+
+/// Check an Atom `<author>`/`<contributor>` person construct.
+fn check_atom_person(report: &mut Report, location: &str, person: &Element) {
+    match person.child_text("name") {
+        Some(name) if !name.is_empty() => {}
+        _ => report.error(location, format!("<{}> has no <name>", person.name)),
+    }
+    if let Some(email) = person.child_text("email")
+        && !is_email(email)
+    {
+        report.error(
+            location,
+            format!("<email> `{email}` is not an email address"),
+        );
+    }
+    if let Some(uri) = person.child_text("uri") {
+        check_url(report, location, "<uri>", uri);
+    }
+}
+
+/// Check `<link>`s, returning the `rel="self"` href if there is one.
+fn check_atom_links<'a>(
+    report: &mut Report,
+    location: &str,
+    parent: &'a Element,
+) -> Option<&'a str> {
+    let mut self_link = None;
+    for link in parent.children("link") {
+        let Some(href) = link.attr("href") else {
+            report.error(location, "<link> has no href");
+            continue;
+        };
+        if !is_absolute_url(href) {
+            report.warning(
+                location,
+                format!("<link> href `{href}` is relative and there is no xml:base"),
+            );
+        }
+        if link.attr("rel") == Some("self") {
+            self_link = Some(href);
+        }
+    }
+    self_link
+}
+
+/// Validate an Atom 1.0 document. See [`validate_feed`] for `self_url`.
+#[must_use]
+pub fn validate_atom(xml: &str, self_url: Option<&str>) -> Report {
+    let mut report = Report::default();
+    let feed = match parse_xml(xml) {
+        Ok(root) => root,
+        Err(msg) => {
+            report.error("document", msg);
+            return report;
+        }
+    };
+    if feed.name != "feed" {
+        report.error(
+            "document",
+            format!("root element is <{}>, expected <feed>", feed.name),
+        );
+        return report;
+    }
+    if feed.attr("xmlns") != Some(ATOM_NS) {
+        report.error(
+            "feed",
+            format!("<feed> is not in the Atom namespace ({ATOM_NS})"),
+        );
+    }
+
+    for name in ["id", "title", "updated"] {
+        if feed.child(name).is_none() {
+            report.error("feed", format!("missing required <{name}>"));
+        }
+    }
+    if let Some(id) = feed.child_text("id") {
+        check_url(&mut report, "feed", "<id>", id);
+    }
+    if let Some(updated) = feed.child_text("updated") {
+        check_rfc3339(&mut report, "feed", "<updated>", updated);
+    }
+    for author in feed.children("author") {
+        check_atom_person(&mut report, "feed", author);
+    }
+    let self_link = check_atom_links(&mut report, "feed", &feed);
+    check_self_link(
+        &mut report,
+        "feed",
+        "<link rel=\"self\">",
+        self_link,
+        self_url,
+    );
+
+    let feed_has_author = feed.child("author").is_some();
+    let mut ids = HashSet::new();
+    for (idx, entry) in feed.children("entry").enumerate() {
+        let loc = format!("entry {}", idx + 1);
+        for name in ["id", "title", "updated"] {
+            if entry.child(name).is_none() {
+                report.error(&loc, format!("missing required <{name}>"));
+            }
+        }
+        if let Some(id) = entry.child_text("id") {
+            check_url(&mut report, &loc, "<id>", id);
+            if !ids.insert(id) {
+                report.error(&loc, format!("duplicate <id> `{id}`"));
+            }
+        }
+        for name in ["updated", "published"] {
+            if let Some(date) = entry.child_text(name) {
+                check_rfc3339(&mut report, &loc, &format!("<{name}>"), date);
+            }
+        }
+        if entry.child("author").is_none() && !feed_has_author {
+            report.error(
+                &loc,
+                "entry has no <author> and the feed has none to inherit",
+            );
+        }
+        for author in entry.children("author") {
+            check_atom_person(&mut report, &loc, author);
+        }
+        check_atom_links(&mut report, &loc, entry);
+        let has_alternate = entry
+            .children("link")
+            .any(|l| matches!(l.attr("rel"), None | Some("alternate")));
+        if entry.child("content").is_none() && !has_alternate {
+            report.error(
+                &loc,
+                "an entry without <content> needs a <link rel=\"alternate\">",
+            );
+        }
+        for name in ["content", "summary"] {
+            if let Some(text) = entry.child(name)
+                && text.attr("type") == Some("html")
+            {
+                check_content_urls(&mut report, &loc, &format!("<{name}>"), &text.text);
+            }
+        }
+    }
+    report
+}
+
+/// Check a JSON Feed `author`/`authors` pair on a feed or item object.
+fn check_json_authors(report: &mut Report, location: &str, object: &Map<String, Value>) {
+    if object.contains_key("author") {
+        report.warning(
+            location,
+            "`author` is deprecated in JSON Feed 1.1, use `authors`",
+        );
+    }
+    let authors = object.get("author").into_iter().chain(
+        object
+            .get("authors")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten(),
+    );
+    for author in authors {
+        let Some(author) = author.as_object() else {
+            report.error(location, "an author must be an object");
+            continue;
+        };
+        if !["name", "url", "avatar"]
+            .iter()
+            .any(|k| author.contains_key(*k))
+        {
+            report.error(location, "an author needs a `name`, `url` or `avatar`");
+        }
+        for key in ["url", "avatar"] {
+            if let Some(url) = author.get(key).and_then(Value::as_str) {
+                check_url(report, location, &format!("author `{key}`"), url);
+            }
+        }
+    }
+}
+
+/// Check optional URL-valued keys of a JSON Feed object.
+fn check_json_urls(
+    report: &mut Report,
+    location: &str,
+    object: &Map<String, Value>,
+    keys: &[&str],
+) {
+    for key in keys {
+        match object.get(*key) {
+            None => {}
+            Some(Value::String(url)) => check_url(report, location, &format!("`{key}`"), url),
+            Some(_) => report.error(location, format!("`{key}` must be a string")),
+        }
+    }
+}
+
+/// Validate a JSON Feed 1.1 document. See [`validate_feed`] for `self_url`.
+#[must_use]
+pub fn validate_json_feed(json: &str, self_url: Option<&str>) -> Report {
+    let mut report = Report::default();
+    let value: Value = match serde_json::from_str(json) {
+        Ok(value) => value,
+        Err(e) => {
+            report.error("document", format!("not valid JSON: {e}"));
+            return report;
+        }
+    };
+    let Some(feed) = value.as_object() else {
+        report.error("document", "a JSON Feed must be an object");
+        return report;
+    };
+
+    match feed.get("version").and_then(Value::as_str) {
+        Some(JSON_FEED_1_1) => {}
+        Some(JSON_FEED_1_0) => report.warning("feed", "JSON Feed 1.0; 1.1 is current"),
+        Some(other) => report.error("feed", format!("unknown `version` `{other}`")),
+        None => report.error("feed", "missing required `version`"),
+    }
+    if !feed.get("title").is_some_and(Value::is_string) {
+        report.error("feed", "missing required `title`");
+    }
+    if !feed.contains_key("home_page_url") {
+        report.warning("feed", "missing `home_page_url`");
+    }
+    check_json_urls(
+        &mut report,
+        "feed",
+        feed,
+        &["home_page_url", "next_url", "icon", "favicon"],
+    );
+    match feed.get("feed_url") {
+        Some(Value::String(url)) => {
+            check_self_link(&mut report, "feed", "`feed_url`", Some(url), self_url);
+        }
+        Some(_) => report.error("feed", "`feed_url` must be a string"),
+        None => check_self_link(&mut report, "feed", "`feed_url`", None, self_url),
+    }
+    check_json_authors(&mut report, "feed", feed);
+
+    let Some(items) = feed.get("items").and_then(Value::as_array) else {
+        report.error("feed", "missing required `items` array");
+        return report;
+    };
+    let mut ids = HashSet::new();
+    for (idx, item) in items.iter().enumerate() {
+        let loc = format!("item {}", idx + 1);
+        let Some(item) = item.as_object() else {
+            report.error(&loc, "an item must be an object");
+            continue;
+        };
+        match item.get("id") {
+            Some(Value::String(id)) => {
+                if !ids.insert(id.as_str()) {
+                    report.error(&loc, format!("duplicate `id` `{id}`"));
+                }
+            }
+            Some(_) => report.error(&loc, "`id` must be a string"),
+            None => report.error(&loc, "missing required `id`"),
+        }
+        if !item.contains_key("content_html") && !item.contains_key("content_text") {
+            report.error(&loc, "an item needs `content_html` or `content_text`");
+        }
+        check_json_urls(
+            &mut report,
+            &loc,
+            item,
+            &["url", "external_url", "image", "banner_image"],
+        );
+        for key in ["date_published", "date_modified"] {
+            if let Some(date) = item.get(key).and_then(Value::as_str) {
+                check_rfc3339(&mut report, &loc, &format!("`{key}`"), date);
+            }
+        }
+        if let Some(html) = item.get("content_html").and_then(Value::as_str) {
+            check_content_urls(&mut report, &loc, "`content_html`", html);
+        }
+        check_json_authors(&mut report, &loc, item);
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSS_SELF: &str = "https://example.com/rss.xml";
+
+    fn rss(items: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom"><channel>
+<title>Book</title><link>https://example.com/</link><description>A book</description>
+<atom:link href="{RSS_SELF}" rel="self"/>
+{items}
+</channel></rss>"#
+        )
+    }
+
+    fn messages(report: &Report) -> Vec<String> {
+        report.issues.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn valid_rss_has_no_issues() {
+        let xml = rss(
+            r#"<item><title>One</title><link>https://example.com/one.html</link>
+<description>&lt;p&gt;Hi &lt;a href="https://example.com/two.html"&gt;two&lt;/a&gt;&lt;/p&gt;</description>
+<guid>https://example.com/one.html</guid><pubDate>Sat, 01 Aug 2026 10:00:00 +0000</pubDate>
+<author>jane@example.com (Jane)</author></item>"#,
+        );
+        let report = validate_feed(&xml, Some(RSS_SELF));
+        assert!(report.issues.is_empty(), "{:?}", messages(&report));
+    }
+
+    #[test]
+    fn rss_reports_bad_dates_authors_and_duplicate_guids() {
+        let item = r"<item><title>One</title><guid>https://example.com/one.html</guid>
+<pubDate>2026-08-01</pubDate><author>Jane</author></item>";
+        let xml = rss(&format!("{item}{item}"));
+        let report = validate_rss(&xml, None);
+        let msgs = messages(&report);
+        assert!(report.has_errors());
+        assert!(msgs.iter().any(|m| m.contains("not a valid RFC 822 date")));
+        assert!(msgs.iter().any(|m| m.contains("must start with an email")));
+        assert!(
+            msgs.iter()
+                .any(|m| m.starts_with("error: item 2: duplicate <guid>"))
+        );
+    }
+
+    #[test]
+    fn rss_reports_missing_channel_elements_and_self_mismatch() {
+        let xml = r#"<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom"><channel>
+<title>Book</title><atom:link href="https://example.com/rss2.xml" rel="self"/>
+</channel></rss>"#;
+        let msgs = messages(&validate_rss(xml, Some(RSS_SELF)));
+        assert!(msgs.contains(&"error: channel: missing required <link>".to_string()));
+        assert!(msgs.contains(&"error: channel: missing required <description>".to_string()));
+        assert!(
+            msgs.iter()
+                .any(|m| m.contains("does not match the feed's URL"))
+        );
+    }
+
+    #[test]
+    fn relative_content_urls_are_warnings() {
+        let xml = rss(r##"<item><title>One</title>
+<description>&lt;img src="img/a.png"&gt; &lt;a href="#x"&gt;&lt;/a&gt; &lt;a data-href="b.html"&gt;&lt;/a&gt;</description></item>"##);
+        let report = validate_rss(&xml, None);
+        assert!(!report.has_errors());
+        assert_eq!(report.count(Severity::Warning), 1);
+        assert!(report.issues[0].message.contains("`img/a.png`"));
+    }
+
+    #[test]
+    fn relative_urls_match_the_rewritten_attributes() {
+        let html = "<img src=a.png srcset='b.png 2x, https://cdn.example.com/c.png 3x'>\
+                    <video poster = \"d.jpg\"></video><a data-href=\"e.html\" \
+                    HREF='f.html'>x</a><div style=\"background: url(g.png)\"></div>";
+        assert_eq!(
+            relative_urls(html),
+            vec!["a.png", "b.png", "d.jpg", "f.html", "g.png"]
+        );
+    }
+
+    #[test]
+    fn malformed_xml_is_an_error() {
+        let report = validate_rss("<rss version=\"2.0\"><channel>", None);
+        assert!(report.has_errors());
+        assert_eq!(report.issues[0].location, "document");
+    }
+
+    #[test]
+    fn atom_requires_ids_dates_and_authors() {
+        let xml = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+<id>https://example.com/atom.xml</id><title>Book</title><updated>2026-08-01T10:00:00Z</updated>
+<link href="https://example.com/atom.xml" rel="self"/>
+<entry><id>https://example.com/a.html</id><title>A</title><updated>yesterday</updated>
+<link href="https://example.com/a.html"/></entry>
+<entry><id>https://example.com/a.html</id><title>B</title><updated>2026-08-01T10:00:00Z</updated>
+<author><name>Jane</name><email>jane</email></author><content type="html">x</content></entry>
+</feed>"#;
+        let msgs = messages(&validate_feed(xml, Some("https://example.com/atom.xml")));
+        assert!(msgs.contains(
+            &"error: entry 1: <updated> `yesterday` is not a valid RFC 3339 date".to_string()
+        ));
+        assert!(
+            msgs.iter()
+                .any(|m| m.starts_with("error: entry 1: entry has no <author>"))
+        );
+        assert!(
+            msgs.iter()
+                .any(|m| m.starts_with("error: entry 2: duplicate <id>"))
+        );
+        assert!(
+            msgs.iter()
+                .any(|m| m.contains("`jane` is not an email address"))
+        );
+        assert_eq!(msgs.len(), 4, "{msgs:?}");
+    }
+
+    #[test]
+    fn json_feed_checks_items_and_feed_url() {
+        let json = r#"{
+            "version": "https://jsonfeed.org/version/1.1",
+            "title": "Book",
+            "home_page_url": "https://example.com/",
+            "feed_url": "https://example.com/feed.json",
+            "items": [
+                {"id": "1", "content_html": "<p>a</p>", "date_published": "2026-08-01T10:00:00+00:00"},
+                {"id": "1", "url": "/b.html", "date_modified": "Aug 1"}
+            ]
+        }"#;
+        let report = validate_feed(json, Some("https://example.com/feed2.json"));
+        let msgs = messages(&report);
+        assert!(
+            msgs.iter()
+                .any(|m| m.contains("`feed_url` `https://example.com/feed.json` does not match"))
+        );
+        assert!(msgs.contains(&"error: item 2: duplicate `id` `1`".to_string()));
+        assert!(
+            msgs.iter()
+                .any(|m| m.contains("needs `content_html` or `content_text`"))
+        );
+        assert!(
+            msgs.iter()
+                .any(|m| m.contains("`url` `/b.html` is not an absolute URL"))
+        );
+        assert!(msgs.iter().any(|m| m.contains("`date_modified` `Aug 1`")));
+        assert_eq!(report.count(Severity::Warning), 0);
+    }
+
+    #[test]
+    fn detects_each_format() {
+        assert_eq!(
+            detect_format("<?xml version=\"1.0\"?>\n<rss/>"),
+            Some(FeedKind::Rss)
+        );
+        assert_eq!(
+            detect_format("<feed xmlns=\"http://www.w3.org/2005/Atom\"/>"),
+            Some(FeedKind::Atom)
+        );
+        assert_eq!(
+            detect_format("  {\"version\": 1}"),
+            Some(FeedKind::JsonFeed)
+        );
+        assert_eq!(detect_format("<html/>"), None);
+        assert!(validate_feed("<html/>", None).has_errors());
+    }
+}